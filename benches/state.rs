@@ -0,0 +1,63 @@
+//! Measures what a request costs in handing the state to its handler.
+//!
+//! `cloned state` hands every request its own copy of the state, like `App::handle` did before
+//! state was shared through an `Arc`; `shared state` is what `App::handle` does now. The state
+//! holds a large catalog, so the difference between the two is the cost of the copy.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::hint::black_box;
+use std::sync::Arc;
+use taiko::body::Body;
+use taiko::prelude::*;
+use tokio::runtime::Runtime;
+
+#[derive(Clone)]
+struct Catalog {
+    items: Vec<u64>,
+}
+
+fn catalog() -> Catalog {
+    Catalog {
+        items: vec![0; 1_000_000],
+    }
+}
+
+fn count(catalog: &Catalog) -> Response {
+    Response::new(Body::from(catalog.items.len().to_string()))
+}
+
+async fn shared(_request: Request, state: Arc<Catalog>) -> Response {
+    count(&state)
+}
+
+async fn cloned(_request: Request, state: Catalog) -> Response {
+    count(&state)
+}
+
+fn request() -> Request {
+    Request::get("/").body(Body::empty()).unwrap()
+}
+
+fn state(c: &mut Criterion) {
+    let runtime = Runtime::new().expect("Could not start runtime");
+    let mut group = c.benchmark_group("state");
+
+    let state = catalog();
+
+    group.bench_function("cloned state", |b| {
+        b.to_async(&runtime)
+            .iter(|| async { black_box(cloned.handle(request(), state.clone()).await) })
+    });
+
+    let app = App::new(catalog()).handler(Router::new().get("/", shared));
+
+    group.bench_function("shared state", |b| {
+        b.to_async(&runtime)
+            .iter(|| async { black_box(app.handle(request()).await) })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, state);
+criterion_main!(benches);
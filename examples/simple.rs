@@ -1,6 +1,8 @@
+use std::sync::Arc;
 use serde::{Deserialize, Serialize};
 use tokio::net::TcpListener;
 use taiko::body::JsonError;
+use taiko::extract::{Path, Query};
 use taiko::prelude::*;
 use taiko::request::Extract;
 use taiko::response::IntoResponse;
@@ -12,7 +14,7 @@ async fn main() {
         .expect("Could not listen to 8080");
 
     let router = Router::new()
-        .get("/users/{test}", index)
+        .get("/users/{id}", show)
         .get("/users/me", index);
 
     App::new(())
@@ -29,7 +31,8 @@ struct Payload {
 
 #[derive(Serialize, Deserialize)]
 enum HttpError {
-    BadRequest
+    BadRequest,
+    UnprocessableEntity(String)
 }
 
 impl IntoResponse for HttpError {
@@ -37,7 +40,10 @@ impl IntoResponse for HttpError {
         match self {
             HttpError::BadRequest => Json(Payload {
                 message: String::from("Bad request")
-            }).into_response()
+            }).into_response(),
+            HttpError::UnprocessableEntity(message) => {
+                (StatusCode::UNPROCESSABLE_ENTITY, Json(Payload { message })).into_response()
+            }
         }
     }
 }
@@ -45,8 +51,10 @@ impl IntoResponse for HttpError {
 impl From<JsonError> for HttpError {
     fn from(value: JsonError) -> Self {
         match value {
-            JsonError::Deserialize => HttpError::BadRequest,
-            JsonError::Serialize => HttpError::BadRequest
+            JsonError::Data { path, message, .. } => {
+                HttpError::UnprocessableEntity(format!("{path}: {message}"))
+            }
+            _ => HttpError::BadRequest
         }
     }
 }
@@ -56,19 +64,44 @@ struct Identity;
 impl<S> Extract<S> for Identity {
     type Error = HttpError;
 
-    fn extract(request: &Request, state: &S) -> impl Future<Output=Result<Self, Self::Error>> {
+    #[allow(clippy::manual_async_fn)]
+    fn extract(_request: &Request, _state: &S) -> impl Future<Output=Result<Self, Self::Error>> + Send {
         async move {
             Ok(Identity)
         }
     }
 }
 
-async fn index(request: Request, _state: ()) -> Result<Json<Payload>, HttpError> {
+async fn index(_request: Request, _state: Arc<()>) -> Result<Json<Payload>, HttpError> {
     Ok(Json(Payload {
         message: String::from("hi")
     }))
 }
 
+#[derive(Deserialize)]
+struct UserPath {
+    id: u32
+}
+
+#[derive(Deserialize)]
+struct Greeting {
+    greeting: Option<String>
+}
+
+async fn show(
+    Path(path): Path<UserPath>,
+    Query(query): Query<Greeting>,
+    _identity: Identity,
+    _body: Empty,
+    _state: Arc<()>
+) -> Result<Json<Payload>, HttpError> {
+    let greeting = query.greeting.unwrap_or_else(|| String::from("hi"));
+
+    Ok(Json(Payload {
+        message: format!("{greeting} {}", path.id)
+    }))
+}
+
 struct LogLayer;
 impl<H> Layer<H> for LogLayer {
     type Handler = LogHandler<H>;
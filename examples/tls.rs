@@ -0,0 +1,37 @@
+//! Serves a route over HTTPS.
+//!
+//! Generate a self-signed certificate for local testing with:
+//!
+//! ```sh
+//! openssl req -x509 -newkey rsa:2048 -nodes -days 30 -subj "/CN=localhost" \
+//!     -keyout key.pem -out cert.pem
+//! ```
+//!
+//! and try it with `curl --insecure https://localhost:8443/`.
+
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use taiko::prelude::*;
+use taiko::tls::TlsConfig;
+
+#[tokio::main]
+async fn main() {
+    let config = TlsConfig::from_pem_file("cert.pem", "key.pem")
+        .expect("Could not load cert.pem and key.pem");
+
+    let listener = TcpListener::bind("0.0.0.0:8443")
+        .await
+        .expect("Could not listen to 8443");
+
+    let router = Router::new()
+        .get("/", index);
+
+    App::new(())
+        .handler(router)
+        .listen_tls(listener, config)
+        .await;
+}
+
+async fn index(_request: Request, _state: Arc<()>) -> Json<&'static str> {
+    Json("hello over tls")
+}
@@ -1,21 +1,50 @@
+use crate::body::Body;
 use crate::handler::{Boxed, EchoHandler};
+use crate::listener::Listener;
+use crate::server::ServerConfig;
+use crate::tls::{TlsConfig, TlsListener};
 use crate::{Handler, Layer, Request, Response};
+use hyper::body::Incoming;
 use hyper::service::Service;
-use hyper_util::rt::{TokioExecutor, TokioIo};
-use hyper_util::server::conn::auto;
+use hyper_util::rt::TokioIo;
 use std::convert::Infallible;
-use std::net::SocketAddr;
+use std::fmt::Debug;
+use std::io;
+use std::io::ErrorKind;
+use std::future::pending;
 use std::pin::Pin;
 use std::sync::Arc;
-use hyper_util::server::conn::auto::Builder;
+use std::time::Duration;
 use hyper_util::server::graceful::GracefulShutdown;
 use tokio::net::TcpListener;
+use tokio::sync::OwnedSemaphorePermit;
+use tokio::task::JoinSet;
+use tokio::time::sleep;
 use tokio::{pin, signal, spawn};
-use tracing::{error, info};
+#[cfg(unix)]
+use tokio::signal::unix::SignalKind;
+use tracing::{debug, error, info, warn};
+
+/// The first delay before accepting again after the listener failed, e.g. on `EMFILE`.
+const MINIMUM_ACCEPT_BACKOFF: Duration = Duration::from_millis(5);
+
+/// The delay between accept attempts stops doubling here.
+const MAXIMUM_ACCEPT_BACKOFF: Duration = Duration::from_secs(1);
 
 pub struct App<S, H> {
     state: Arc<S>,
     handler: H,
+    drain_timeout: Option<Duration>,
+    config: ServerConfig,
+}
+
+/// How the open connections ended when the server shut down.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShutdownSummary {
+    /// Connections that completed on their own after shutdown started.
+    pub drained: usize,
+    /// Connections that were still open at the drain deadline and were closed forcibly.
+    pub aborted: usize,
 }
 
 impl<S> App<S, EchoHandler>
@@ -25,16 +54,20 @@ impl<S> App<S, EchoHandler>
         App {
             state: Arc::new(state),
             handler: EchoHandler,
+            drain_timeout: None,
+            config: ServerConfig::default(),
         }
     }
 
     pub fn handler<H>(self, handler: H) -> App<S, H>
     where
-        H: Handler<Request, S>
+        H: Handler<Request, Arc<S>>
     {
         App {
             state: self.state,
-            handler
+            handler,
+            drain_timeout: self.drain_timeout,
+            config: self.config,
         }
     }
 }
@@ -47,102 +80,301 @@ impl<S, H> App<S, H> {
         App {
             state: self.state,
             handler: layer.wrap(self.handler),
+            drain_timeout: self.drain_timeout,
+            config: self.config,
         }
     }
 
-    fn boxed<I, O>(self) -> App<S, Boxed<H>>
+    /// Limits how long shutdown waits for open connections to complete. Connections still open
+    /// after `timeout` are closed forcibly. Without a timeout, shutdown waits indefinitely.
+    pub fn drain_timeout(mut self, timeout: Duration) -> Self {
+        self.drain_timeout = Some(timeout);
+        self
+    }
+
+    /// Configures the HTTP connections the app serves.
+    pub fn server_config(mut self, config: ServerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub(crate) fn boxed<I, O>(self) -> App<S, Boxed<H>>
     where
-        H: Handler<I, S, Output=O>,
+        H: Handler<I, Arc<S>, Output=O>,
     {
         App {
             state: self.state,
             handler: Boxed::new(self.handler),
+            drain_timeout: self.drain_timeout,
+            config: self.config,
         }
     }
 }
 
-impl<S, H> Clone for App<S, Boxed<H>>
-where
-    S: Clone,
-{
+impl<S, H> Clone for App<S, Boxed<H>> {
     fn clone(&self) -> Self {
         Self {
             state: self.state.clone(),
             handler: self.handler.clone(),
+            drain_timeout: self.drain_timeout,
+            config: self.config.clone(),
         }
     }
 }
 
 impl<S, H> App<S, H>
 where
-    S: Clone + Send + Sync + 'static,
-    H: Handler<Request, S, Output=Response> + Send + Sync + 'static,
+    S: Send + Sync + 'static,
+    H: Handler<Request, Arc<S>, Output=Response> + Send + Sync + 'static,
     H::Future: Send,
 {
-    pub async fn listen(self, listener: TcpListener) {
+    /// Serves connections from `listener` until the process receives `SIGINT` or `SIGTERM`,
+    /// then waits for the open connections to complete.
+    pub async fn listen<L>(self, listener: L) -> ShutdownSummary
+    where
+        L: Listener,
+    {
+        self.listen_with_shutdown(listener, shutdown_signal()).await
+    }
+
+    /// Serves connections from `listener` until `shutdown` completes, then waits for the open
+    /// connections to complete.
+    ///
+    /// When shutdown starts, idle keep-alive connections are closed, HTTP/1 responses still in
+    /// flight are sent with `Connection: close` and HTTP/2 clients receive a `GOAWAY`.
+    pub async fn listen_with_shutdown<L>(self, mut listener: L, shutdown: impl Future<Output=()>) -> ShutdownSummary
+    where
+        L: Listener,
+    {
         info!("Listening...");
+        let drain_timeout = self.drain_timeout;
+        let upgrades = self.config.supports_upgrades();
+        let limit = self.config.connection_limit();
+        let http = self.config.builder();
         let app = self.boxed();
         let graceful = GracefulShutdown::new();
-        let http = Builder::new(TokioExecutor::new());
+        let mut connections = JoinSet::new();
+
+        pin!(shutdown);
+
+        let mut backoff = MINIMUM_ACCEPT_BACKOFF;
 
         loop {
+            let permit = match &limit {
+                None => None,
+                Some(limit) => match limit.clone().try_acquire_owned() {
+                    Ok(permit) => Some(permit),
+                    Err(_) => {
+                        warn!(
+                            monotonic_counter.connection_limit_reached = 1u64,
+                            "Connection limit reached, waiting for a connection to close."
+                        );
+
+                        tokio::select! {
+                            _ = &mut shutdown => {
+                                break;
+                            }
+                            permit = limit.clone().acquire_owned() => {
+                                Some(permit.expect("Connection limit is never closed"))
+                            }
+                        }
+                    }
+                },
+            };
+
             tokio::select! {
-                _ = signal::ctrl_c() => {
+                _ = &mut shutdown => {
                     break;
                 }
-                Ok((stream, client)) = listener.accept() => {
-                    let app = app.clone();
-                    let io = TokioIo::new(stream);
-                    let connection = graceful.watch(http
-                        .serve_connection_with_upgrades(io, AppService(app, client))
-                        .into_owned());
+                accepted = listener.accept() => {
+                    let (stream, client) = match accepted {
+                        Ok(accepted) => accepted,
+                        Err(err) if is_connection_error(&err) => {
+                            debug!("Could not accept connection: {:?}", err);
+                            continue;
+                        }
+                        Err(err) => {
+                            error!("Could not accept connection, retrying in {:?}: {:?}", backoff, err);
 
-                    spawn(async move {
-                        if let Err(err) = connection.await {
-                            error!("Connection error: {:?}", err);
+                            tokio::select! {
+                                _ = &mut shutdown => {
+                                    break;
+                                }
+                                _ = sleep(backoff) => {}
+                            }
+
+                            backoff = (backoff * 2).min(MAXIMUM_ACCEPT_BACKOFF);
+                            continue;
                         }
-                    });
+                    };
+
+                    backoff = MINIMUM_ACCEPT_BACKOFF;
+
+                    let service = AppService(app.clone(), client);
+                    let io = TokioIo::new(stream);
+
+                    if upgrades {
+                        let connection = graceful.watch(http
+                            .serve_connection_with_upgrades(io, service)
+                            .into_owned());
+
+                        connections.spawn(drive_connection(connection, permit));
+                    } else {
+                        let connection = graceful.watch(http
+                            .serve_connection(io, service)
+                            .into_owned());
+
+                        connections.spawn(drive_connection(connection, permit));
+                    }
+                }
+                Some(_) = connections.join_next() => {}
+            }
+        }
+
+        // Connections that closed before shutdown started are not drained ones.
+        while connections.try_join_next().is_some() {}
+
+        info!("Waiting for {} connections to complete...", connections.len());
+        spawn(graceful.shutdown());
+
+        let deadline = async {
+            match drain_timeout {
+                Some(timeout) => sleep(timeout).await,
+                None => pending().await,
+            }
+        };
+
+        pin!(deadline);
+
+        let mut summary = ShutdownSummary::default();
+
+        loop {
+            tokio::select! {
+                connection = connections.join_next() => match connection {
+                    Some(Ok(())) => summary.drained += 1,
+                    Some(Err(err)) => error!("Connection task failed while draining: {:?}", err),
+                    None => break,
+                },
+                _ = &mut deadline => {
+                    summary.aborted = connections.len();
+                    warn!("Closing {} connections that did not complete in time.", summary.aborted);
+                    connections.shutdown().await;
+                    break;
                 }
             }
         }
 
-        info!("Waiting for connections to complete...");
-        graceful.shutdown().await;
-        info!("All connections closed. Shutting down.");
+        info!(
+            "All connections closed ({} drained, {} aborted). Shutting down.",
+            summary.drained, summary.aborted
+        );
+
+        summary
+    }
+
+    /// Serves TLS connections from `listener`, negotiating HTTP/2 or HTTP/1.1 through ALPN.
+    ///
+    /// The handshakes use the defaults of [`TlsListener`]. To change them, e.g. how many
+    /// handshakes run at once, pass the listener to [`listen`](Self::listen) instead:
+    /// `app.listen(TlsListener::new(listener, config).max_handshakes(64))`.
+    pub async fn listen_tls(self, listener: TcpListener, config: TlsConfig) -> ShutdownSummary {
+        self.listen(TlsListener::new(listener, config)).await
     }
 }
 
-impl<S, H> App<S, Boxed<H>>
+impl<S, H> App<S, H>
 where
-    S: Clone + Send + Sync + 'static,
-    H: Handler<Request, S, Output=Response>,
+    S: Send + Sync + 'static,
+    H: Handler<Request, Arc<S>, Output=Response>,
 {
+    /// Runs `request` through the handler, without a connection.
     pub fn handle(&self, request: Request) -> H::Future {
-        self.handler.inner().handle(request, self.state.as_ref().clone())
+        self.handler.handle(request, self.state.clone())
     }
 }
 
-#[derive(Clone)]
-pub struct AppService<S, H>(App<S, Boxed<H>>, SocketAddr)
+pub(crate) struct AppService<S, H, A>(pub(crate) App<S, Boxed<H>>, pub(crate) A)
+where
+    S: Send + Sync + 'static,
+    H: Handler<Request, Arc<S>, Output=Response>;
+
+impl<S, H, A> Clone for AppService<S, H, A>
 where
-    S: Clone + Send + Sync + 'static,
-    H: Handler<Request, S, Output=Response>;
+    S: Send + Sync + 'static,
+    H: Handler<Request, Arc<S>, Output=Response>,
+    A: Clone,
+{
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), self.1.clone())
+    }
+}
 
-impl<S, H> Service<Request> for AppService<S, H>
+impl<S, H, A> Service<Request<Incoming>> for AppService<S, H, A>
 where
-    S: Clone + Send + Sync + 'static,
-    H: Handler<Request, S, Output=Response>,
+    S: Send + Sync + 'static,
+    H: Handler<Request, Arc<S>, Output=Response>,
     H::Future: Send + 'static,
+    A: Clone + Send + Sync + 'static,
 {
     type Response = Response;
     type Error = Infallible;
     type Future =
     Pin<Box<dyn Future<Output=Result<Self::Response, Self::Error>> + Send + 'static>>;
 
-    fn call(&self, mut req: Request) -> Self::Future {
-        req.extensions_mut().insert(self.1);
+    fn call(&self, req: Request<Incoming>) -> Self::Future {
+        let mut req = req.map(Body::from);
+        req.extensions_mut().insert(self.1.clone());
 
-        let future = self.0.handle(req);
+        let future = self.0.handler.inner().handle(req, self.0.state.clone());
         Box::pin(async move { Ok(future.await) })
     }
 }
+
+/// Runs a connection to completion, holding its slot in the connection limit until it closes.
+async fn drive_connection<E>(connection: impl Future<Output=Result<(), E>>, _permit: Option<OwnedSemaphorePermit>)
+where
+    E: Debug,
+{
+    if let Err(err) = connection.await {
+        error!("Connection error: {:?}", err);
+    }
+}
+
+/// Errors that only concern the connection being accepted, after which the listener can be used
+/// again right away.
+fn is_connection_error(err: &io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::ConnectionRefused | ErrorKind::ConnectionAborted | ErrorKind::ConnectionReset
+    )
+}
+
+async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(err) = signal::ctrl_c().await {
+            error!("Could not listen for SIGINT: {:?}", err);
+            pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match signal::unix::signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(err) => {
+                error!("Could not listen for SIGTERM: {:?}", err);
+                pending::<()>().await;
+            }
+        }
+    };
+
+    #[cfg(not(unix))]
+    let terminate = pending::<()>();
+
+    tokio::select! {
+        _ = interrupt => {}
+        _ = terminate => {}
+    }
+}
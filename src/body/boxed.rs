@@ -0,0 +1,138 @@
+use futures_core::Stream;
+use futures_util::TryStreamExt;
+use sync_wrapper::SyncStream;
+use http_body_util::combinators::BoxBody;
+use http_body_util::{BodyExt, Full};
+use hyper::body::{Body as HttpBody, Bytes, Frame, Incoming, SizeHint};
+use std::error::Error;
+use std::fmt::{Debug, Formatter};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use crate::response::IntoResponse;
+use crate::Response;
+
+/// The error type of a [`Body`].
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+/// The body of a request or response, whatever it came from.
+///
+/// It can wrap the body hyper received, a buffered body, a stream of chunks, or any other
+/// [`http_body::Body`](HttpBody) with `Bytes` chunks. Middleware can replace the body of a
+/// request with a transformed one, and the handlers after it consume that instead.
+pub struct Body(BoxBody<Bytes, BoxError>);
+
+impl Body {
+    pub fn new<B>(body: B) -> Self
+    where
+        B: HttpBody<Data=Bytes> + Send + Sync + 'static,
+        B::Error: Into<BoxError>,
+    {
+        Self(body.map_err(Into::into).boxed())
+    }
+
+    pub fn empty() -> Self {
+        Self::new(http_body_util::Empty::new())
+    }
+
+    /// A body made of the chunks `stream` yields, until it ends or yields an error. The chunks
+    /// are sent as they come, without buffering the whole body.
+    pub fn from_stream<S, E>(stream: S) -> Self
+    where
+        S: Stream<Item=Result<Bytes, E>> + Send + 'static,
+        E: Into<BoxError>,
+    {
+        // The stream is only polled through `Pin<&mut>`, so it does not need to be `Sync`.
+        let stream = SyncStream::new(stream.map_ok(Frame::data));
+        Self::new(http_body_util::StreamBody::new(stream))
+    }
+}
+
+impl HttpBody for Body {
+    type Data = Bytes;
+    type Error = BoxError;
+
+    fn poll_frame(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame<Self::Data>, Self::Error>>> {
+        Pin::new(&mut self.0).poll_frame(cx)
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.0.is_end_stream()
+    }
+
+    fn size_hint(&self) -> SizeHint {
+        self.0.size_hint()
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl Debug for Body {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Body").finish_non_exhaustive()
+    }
+}
+
+impl From<Incoming> for Body {
+    fn from(body: Incoming) -> Self {
+        Self::new(body)
+    }
+}
+
+impl From<Full<Bytes>> for Body {
+    fn from(body: Full<Bytes>) -> Self {
+        Self::new(body)
+    }
+}
+
+impl From<Bytes> for Body {
+    fn from(bytes: Bytes) -> Self {
+        Self::new(Full::new(bytes))
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::from(Bytes::from(bytes))
+    }
+}
+
+impl From<String> for Body {
+    fn from(text: String) -> Self {
+        Self::from(Bytes::from(text))
+    }
+}
+
+impl From<&'static str> for Body {
+    fn from(text: &'static str) -> Self {
+        Self::from(Bytes::from_static(text.as_bytes()))
+    }
+}
+
+impl IntoResponse for Body {
+    fn into_response(self) -> Response {
+        Response::new(self)
+    }
+}
+
+/// Responds with the chunks a [`Stream`] yields as they come, e.g. for large exports or
+/// proxied downloads.
+///
+/// When the stream yields an error, the response is cut off.
+pub struct StreamBody<S>(pub S);
+
+impl<S, E> IntoResponse for StreamBody<S>
+where
+    S: Stream<Item=Result<Bytes, E>> + Send + 'static,
+    E: Into<BoxError>,
+{
+    fn into_response(self) -> Response {
+        Response::new(Body::from_stream(self.0))
+    }
+}
@@ -0,0 +1,24 @@
+use crate::body::limit::{read_body, BodyError};
+use crate::request::{Consume, Request};
+use hyper::body::Bytes;
+
+impl Consume for Bytes {
+    type Error = BodyError;
+
+    #[allow(clippy::manual_async_fn)]
+    fn consume(request: Request) -> impl Future<Output=Result<Self, Self::Error>> + Send + 'static {
+        async move { read_body(request).await }
+    }
+}
+
+impl Consume for String {
+    type Error = BodyError;
+
+    #[allow(clippy::manual_async_fn)]
+    fn consume(request: Request) -> impl Future<Output=Result<Self, Self::Error>> + Send + 'static {
+        async move {
+            let bytes = read_body(request).await?;
+            String::from_utf8(bytes.to_vec()).map_err(|_| BodyError::InvalidUtf8)
+        }
+    }
+}
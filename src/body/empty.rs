@@ -2,14 +2,13 @@ use std::fmt::{Display, Formatter};
 use crate::request::{Consume, Request};
 use crate::response::{IntoResponse};
 use crate::Response;
-use http_body_util::Full;
-use hyper::body::Bytes;
+use crate::body::Body;
 
 pub struct Empty;
 
 impl IntoResponse for Empty {
     fn into_response(self) -> Response {
-        Response::new(Full::new(Bytes::new()))
+        Response::new(Body::empty())
     }
 }
 
@@ -0,0 +1,119 @@
+use std::fmt::{Display, Formatter};
+use std::ops::Deref;
+use crate::request::{Consume, Request};
+use crate::response::{text_response, IntoResponse};
+use http::header::CONTENT_TYPE;
+use http::{HeaderValue, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::error;
+use crate::Response;
+use crate::body::Body;
+use crate::body::limit::{read_body, BodyError};
+
+const FORM_URLENCODED: &str = "application/x-www-form-urlencoded";
+
+/// An `application/x-www-form-urlencoded` body.
+pub struct Form<T>(pub T);
+
+impl<T> Deref for Form<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> IntoResponse for Form<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        let encoded = match serde_urlencoded::to_string(&self.0) {
+            Ok(encoded) => encoded,
+            Err(err) => {
+                error!("Could not serialize form: {}", err);
+
+                let mut response = Response::new(Body::empty());
+                *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+                return response;
+            }
+        };
+
+        let mut response = Response::new(Body::from(encoded));
+
+        response
+            .headers_mut()
+            .append(CONTENT_TYPE, HeaderValue::from_static(FORM_URLENCODED));
+
+        response
+    }
+}
+
+impl<T> Consume for Form<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = FormError;
+
+    #[allow(clippy::manual_async_fn)]
+    fn consume(request: Request) -> impl Future<Output=Result<Self, Self::Error>> + Send + 'static {
+        async move {
+            if !has_form_content_type(&request) {
+                return Err(FormError::UnsupportedMediaType);
+            }
+
+            let bytes = read_body(request)
+                .await
+                .map_err(FormError::Body)?;
+
+            let deserialized = serde_urlencoded::from_bytes(bytes.as_ref())
+                .map_err(|e| FormError::Deserialize(e.to_string()))?;
+
+            Ok(Form(deserialized))
+        }
+    }
+}
+
+/// Whether the media type of the request, ignoring its parameters, is
+/// `application/x-www-form-urlencoded`.
+fn has_form_content_type(request: &Request) -> bool {
+    request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .is_some_and(|essence| essence.trim().eq_ignore_ascii_case(FORM_URLENCODED))
+}
+
+#[derive(Debug)]
+pub enum FormError {
+    /// The body could not be read.
+    Body(BodyError),
+    /// The request has no `application/x-www-form-urlencoded` content type.
+    UnsupportedMediaType,
+    /// The body could not be decoded into the expected type.
+    Deserialize(String),
+}
+
+impl Display for FormError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FormError::Body(err) => write!(f, "{err}"),
+            FormError::UnsupportedMediaType => write!(f, "Expected content type `{FORM_URLENCODED}`"),
+            FormError::Deserialize(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl IntoResponse for FormError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            FormError::Body(err) => return err.into_response(),
+            FormError::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            FormError::Deserialize(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        };
+
+        text_response(status, self.to_string())
+    }
+}
@@ -4,13 +4,24 @@ use crate::request::{Consume, Request};
 use crate::response::{IntoResponse};
 use http::header::CONTENT_TYPE;
 use http::{HeaderValue, StatusCode};
-use http_body_util::BodyExt;
-use http_body_util::Full;
-use hyper::body::Bytes;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use serde_json::error::Category;
+use serde_json::json;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures_core::Stream;
+use hyper::body::Bytes;
+use tracing::error;
 use crate::Response;
+use crate::body::Body;
+use crate::body::limit::{read_body, BodyError};
 
+/// A JSON body, or a response that serializes to one.
+///
+/// As a consumer, requests must have an `application/json` or `+json` content type, or are
+/// answered with `415 Unsupported Media Type`. See [`LenientJson`] for clients that send other
+/// content types.
 pub struct Json<T>(pub T);
 
 impl<T> Deref for Json<T> {
@@ -21,17 +32,72 @@ impl<T> Deref for Json<T> {
     }
 }
 
+/// Responds with `500 Internal Server Error` when the value cannot be serialized, e.g. a map
+/// with non-string keys.
 impl<T> IntoResponse for Json<T>
 where
     T: Serialize,
 {
     fn into_response(self) -> Response {
-        let bytes = serde_json::to_vec(&self.0)
-            .expect("Serializable value could not serialize");
-        
-        let mut response = Response::new(
-            Full::new(Bytes::from(bytes))
-        );
+        json_response(serde_json::to_vec(&self.0))
+    }
+}
+
+/// A JSON response that is indented for reading, e.g. for debugging endpoints.
+pub struct PrettyJson<T>(pub T);
+
+impl<T> IntoResponse for PrettyJson<T>
+where
+    T: Serialize,
+{
+    fn into_response(self) -> Response {
+        json_response(serde_json::to_vec_pretty(&self.0))
+    }
+}
+
+fn json_response(serialized: serde_json::Result<Vec<u8>>) -> Response {
+    let mut response = match serialized {
+        Ok(bytes) => Response::new(Body::from(bytes)),
+        Err(err) => {
+            error!("Could not serialize JSON response: {}", err);
+
+            let mut response = Response::new(Body::empty());
+            *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+            return response;
+        }
+    };
+
+    response
+        .headers_mut()
+        .append(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+    response
+}
+
+/// A JSON array response that is serialized while it is sent, one chunk of items at a time, so
+/// large collections are never held in memory as JSON all at once.
+///
+/// The status and headers are sent before the items are serialized, so when an item fails to
+/// serialize, the error is logged and the response is cut off.
+pub struct JsonStream<I>(pub I);
+
+/// The size at which a chunk of serialized items is sent.
+const JSON_STREAM_CHUNK_SIZE: usize = 8 * 1024;
+
+impl<I> IntoResponse for JsonStream<I>
+where
+    I: IntoIterator,
+    I::IntoIter: Unpin + Send + 'static,
+    I::Item: Serialize,
+{
+    fn into_response(self) -> Response {
+        let stream = JsonArrayStream {
+            items: self.0.into_iter(),
+            first: true,
+            done: false,
+        };
+
+        let mut response = Response::new(Body::from_stream(stream));
 
         response
             .headers_mut()
@@ -41,6 +107,53 @@ where
     }
 }
 
+struct JsonArrayStream<I> {
+    items: I,
+    first: bool,
+    done: bool,
+}
+
+impl<I> Stream for JsonArrayStream<I>
+where
+    I: Iterator + Unpin,
+    I::Item: Serialize,
+{
+    type Item = Result<Bytes, serde_json::Error>;
+
+    fn poll_next(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        let mut chunk = Vec::with_capacity(JSON_STREAM_CHUNK_SIZE);
+
+        while chunk.len() < JSON_STREAM_CHUNK_SIZE {
+            let Some(item) = this.items.next() else {
+                if this.first {
+                    chunk.push(b'[');
+                }
+
+                chunk.push(b']');
+                this.done = true;
+                break;
+            };
+
+            chunk.push(if this.first { b'[' } else { b',' });
+            this.first = false;
+
+            if let Err(err) = serde_json::to_writer(&mut chunk, &item) {
+                error!("Could not serialize JSON stream item: {}", err);
+                this.done = true;
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+
+        Poll::Ready(Some(Ok(Bytes::from(chunk))))
+    }
+}
+
 impl<T> Consume for Json<T>
 where
     T: DeserializeOwned,
@@ -50,32 +163,185 @@ where
     #[allow(clippy::manual_async_fn)]
     fn consume(request: Request) -> impl Future<Output=Result<Self, Self::Error>> + Send + 'static {
         async move {
-            let bytes = request
-                .collect()
-                .await
-                .map_err(|e| JsonError(e.to_string()))?
-                .to_bytes();
-
-            let serialized = serde_json::from_slice(bytes.as_ref())
-                .map_err(|e| JsonError(e.to_string()))?;
+            if !has_json_content_type(&request) {
+                return Err(JsonError::MissingContentType);
+            }
 
-            Ok(Json(serialized))
+            Ok(Json(deserialize(request).await?))
         }
     }
 }
 
-pub struct JsonError(pub String);
+/// A JSON body, read whatever the content type of the request is.
+///
+/// Meant for clients that send JSON as `text/plain` or without a content type; prefer [`Json`].
+pub struct LenientJson<T>(pub T);
+
+impl<T> Deref for LenientJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<T> Consume for LenientJson<T>
+where
+    T: DeserializeOwned,
+{
+    type Error = JsonError;
+
+    #[allow(clippy::manual_async_fn)]
+    fn consume(request: Request) -> impl Future<Output=Result<Self, Self::Error>> + Send + 'static {
+        async move { Ok(LenientJson(deserialize(request).await?)) }
+    }
+}
+
+async fn deserialize<T>(request: Request) -> Result<T, JsonError>
+where
+    T: DeserializeOwned,
+{
+    let bytes = read_body(request)
+        .await
+        .map_err(JsonError::Body)?;
+
+    let mut deserializer = serde_json::Deserializer::from_slice(bytes.as_ref());
+
+    let value = serde_path_to_error::deserialize(&mut deserializer).map_err(|err| {
+        let path = err.path().to_string();
+        JsonError::new(err.into_inner(), Some(path))
+    })?;
+
+    deserializer
+        .end()
+        .map_err(|err| JsonError::new(err, None))?;
+
+    Ok(value)
+}
+
+/// Whether the media type of the request, ignoring its parameters, is `application/json` or has
+/// a `+json` suffix, like `application/problem+json`.
+fn has_json_content_type(request: &Request) -> bool {
+    let Some(essence) = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|essence| essence.trim().to_ascii_lowercase())
+    else {
+        return false;
+    };
+
+    match essence.split_once('/') {
+        Some(("application", "json")) => true,
+        Some((_, subtype)) => subtype.ends_with("+json"),
+        None => false,
+    }
+}
+
+/// Why a request body could not be read as JSON.
+///
+/// Responds with a JSON body describing the error, e.g.
+/// `{"error":"data","message":"invalid type: string \"x\", expected u32","path":"user.age","line":1,"column":22}`.
+#[derive(Debug)]
+pub enum JsonError {
+    /// The body could not be read.
+    Body(BodyError),
+    /// The request has no JSON content type, or none at all.
+    MissingContentType,
+    /// The body is not valid JSON.
+    Syntax { message: String, line: usize, column: usize },
+    /// The body is valid JSON, but does not match the expected type. `path` leads to the value
+    /// that does not match, like `items[2].name`.
+    Data { message: String, path: String, line: usize, column: usize },
+    /// The body ended before the JSON did.
+    Eof { line: usize, column: usize },
+}
+
+impl JsonError {
+    fn new(err: serde_json::Error, path: Option<String>) -> Self {
+        let (line, column) = (err.line(), err.column());
+
+        let message = err.to_string();
+        let message = message
+            .strip_suffix(&format!(" at line {line} column {column}"))
+            .unwrap_or(&message)
+            .to_string();
+
+        match (err.classify(), path) {
+            (Category::Eof, _) => JsonError::Eof { line, column },
+            (Category::Data, Some(path)) => JsonError::Data { message, path, line, column },
+            _ => JsonError::Syntax { message, line, column },
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            JsonError::Body(_) => "body",
+            JsonError::MissingContentType => "content_type",
+            JsonError::Syntax { .. } => "syntax",
+            JsonError::Data { .. } => "data",
+            JsonError::Eof { .. } => "eof",
+        }
+    }
+
+    fn status(&self) -> StatusCode {
+        match self {
+            JsonError::Body(err) => err.status(),
+            JsonError::MissingContentType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            JsonError::Syntax { .. } | JsonError::Eof { .. } => StatusCode::BAD_REQUEST,
+            JsonError::Data { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
 
 impl Display for JsonError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)
+        match self {
+            JsonError::Body(err) => write!(f, "{err}"),
+            JsonError::MissingContentType => write!(f, "Expected content type `application/json`"),
+            JsonError::Syntax { message, line, column } => {
+                write!(f, "{message} at line {line} column {column}")
+            }
+            JsonError::Data { message, path, line, column } => {
+                write!(f, "{path}: {message} at line {line} column {column}")
+            }
+            JsonError::Eof { line, column } => {
+                write!(f, "Unexpected end of JSON at line {line} column {column}")
+            }
+        }
     }
 }
 
 impl IntoResponse for JsonError {
     fn into_response(self) -> Response {
-        let mut response = Response::new(Full::new(Bytes::new()));
-        *response.status_mut() = StatusCode::BAD_REQUEST;
+        let mut body = json!({ "error": self.kind() });
+
+        match &self {
+            JsonError::Body(_) | JsonError::MissingContentType => {
+                body["message"] = json!(self.to_string());
+            }
+            JsonError::Syntax { message, line, column } => {
+                body["message"] = json!(message);
+                body["line"] = json!(line);
+                body["column"] = json!(column);
+            }
+            JsonError::Data { message, path, line, column } => {
+                body["message"] = json!(message);
+                body["path"] = json!(path);
+                body["line"] = json!(line);
+                body["column"] = json!(column);
+            }
+            JsonError::Eof { line, column } => {
+                body["message"] = json!("Unexpected end of JSON");
+                body["line"] = json!(line);
+                body["column"] = json!(column);
+            }
+        }
+
+        let mut response = Json(body).into_response();
+        *response.status_mut() = self.status();
+
         response
     }
 }
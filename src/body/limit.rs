@@ -0,0 +1,141 @@
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use crate::handler::RouteHandler;
+use crate::request::Request;
+use crate::response::{text_response, IntoResponse};
+use crate::{Handler, Layer, Response};
+use http::header::CONTENT_LENGTH;
+use http::StatusCode;
+use http_body_util::{BodyExt, LengthLimitError, Limited};
+use hyper::body::Bytes;
+use std::pin::Pin;
+
+/// The most bytes a consumer reads from a request body when no other limit is set.
+pub const DEFAULT_BODY_LIMIT: usize = 2 * 1024 * 1024;
+
+/// The most bytes [`Json`](crate::body::Json), [`Bytes`], `String` and other consumers read from
+/// a request body before answering `413 Payload Too Large`.
+///
+/// The limit is read from the request extensions. It can be set for the whole app with
+/// `App::layer(BodyLimit(..))`, for the routes of a router with
+/// [`Router::body_limit`](crate::Router::body_limit), and for a single route with
+/// `BodyLimit(..).wrap(handler)`. The limit closest to the handler wins.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BodyLimit(pub usize);
+
+impl Default for BodyLimit {
+    fn default() -> Self {
+        Self(DEFAULT_BODY_LIMIT)
+    }
+}
+
+impl<H> Layer<H> for BodyLimit {
+    type Handler = BodyLimited<H>;
+
+    fn wrap(self, handler: H) -> Self::Handler {
+        BodyLimited {
+            limit: self,
+            handler,
+        }
+    }
+}
+
+/// A handler that sets the [`BodyLimit`] of the requests it handles.
+#[derive(Clone)]
+pub struct BodyLimited<H> {
+    limit: BodyLimit,
+    handler: H,
+}
+
+impl<S, H> Handler<Request, S> for BodyLimited<H>
+where
+    H: Handler<Request, S>,
+{
+    type Output = H::Output;
+    type Future = H::Future;
+
+    fn handle(&self, mut input: Request, state: S) -> Self::Future {
+        input.extensions_mut().insert(self.limit);
+        self.handler.handle(input, state)
+    }
+}
+
+impl<T, S, H> RouteHandler<T, S> for BodyLimited<H>
+where
+    H: RouteHandler<T, S>,
+{
+    fn call(&self, mut request: Request, state: Arc<S>) -> Pin<Box<dyn Future<Output=Response> + Send>> {
+        request.extensions_mut().insert(self.limit);
+        self.handler.call(request, state)
+    }
+}
+
+/// The request body could not be read.
+#[derive(Debug)]
+pub enum BodyError {
+    /// The body is longer than the [`BodyLimit`] of the request.
+    TooLarge { limit: usize },
+    /// The body was cut off or could not be received.
+    Read(String),
+    /// The body was expected to be text, but is not valid UTF-8.
+    InvalidUtf8,
+}
+
+impl Display for BodyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BodyError::TooLarge { limit } => write!(f, "Request body is larger than {limit} bytes"),
+            BodyError::Read(message) => write!(f, "Could not read request body: {message}"),
+            BodyError::InvalidUtf8 => write!(f, "Request body is not valid UTF-8"),
+        }
+    }
+}
+
+impl BodyError {
+    pub(crate) fn status(&self) -> StatusCode {
+        match self {
+            BodyError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            BodyError::Read(_) | BodyError::InvalidUtf8 => StatusCode::BAD_REQUEST,
+        }
+    }
+}
+
+impl IntoResponse for BodyError {
+    fn into_response(self) -> Response {
+        text_response(self.status(), self.to_string())
+    }
+}
+
+/// The [`BodyLimit`] of `request`, failing early when its `Content-Length` is already too large.
+pub(crate) fn body_limit(request: &Request) -> Result<usize, BodyError> {
+    let BodyLimit(limit) = request
+        .extensions()
+        .get::<BodyLimit>()
+        .copied()
+        .unwrap_or_default();
+
+    let declared = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|length| length.to_str().ok())
+        .and_then(|length| length.parse::<usize>().ok());
+
+    match declared {
+        Some(length) if length > limit => Err(BodyError::TooLarge { limit }),
+        _ => Ok(limit),
+    }
+}
+
+/// Reads the whole body of `request`, up to its [`BodyLimit`].
+pub(crate) async fn read_body(request: Request) -> Result<Bytes, BodyError> {
+    let limit = body_limit(&request)?;
+
+    Limited::new(request.into_body(), limit)
+        .collect()
+        .await
+        .map(|collected| collected.to_bytes())
+        .map_err(|err| match err.downcast_ref::<LengthLimitError>() {
+            Some(_) => BodyError::TooLarge { limit },
+            None => BodyError::Read(err.to_string()),
+        })
+}
@@ -1,6 +1,26 @@
+mod boxed;
+mod bytes;
 mod json;
 mod empty;
+mod form;
+mod limit;
+mod multipart;
 
+pub use self::boxed::Body;
+pub use self::boxed::BoxError;
+pub use self::boxed::StreamBody;
 pub use self::empty::Empty;
+pub use self::form::Form;
+pub use self::form::FormError;
 pub use self::json::Json;
 pub use self::json::JsonError;
+pub use self::json::JsonStream;
+pub use self::json::LenientJson;
+pub use self::json::PrettyJson;
+pub use self::limit::BodyError;
+pub use self::limit::BodyLimit;
+pub use self::limit::BodyLimited;
+pub use self::limit::DEFAULT_BODY_LIMIT;
+pub use self::multipart::Field;
+pub use self::multipart::Multipart;
+pub use self::multipart::MultipartError;
@@ -0,0 +1,246 @@
+use std::fmt::{Display, Formatter};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use crate::request::{Consume, Request};
+use crate::response::{text_response, IntoResponse};
+use futures_core::Stream;
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, StatusCode};
+use http_body_util::BodyExt;
+use hyper::body::Bytes;
+use multer::{Constraints, SizeLimit};
+use serde::de::DeserializeOwned;
+use crate::Response;
+use crate::body::limit::{body_limit, BodyError};
+
+/// A `multipart/form-data` body, read one field at a time.
+///
+/// The whole body is limited by the [`BodyLimit`](crate::body::BodyLimit) of the request. Single
+/// fields can be limited further with [`Multipart::field_limit`].
+pub struct Multipart {
+    inner: multer::Multipart<'static>,
+    field_limit: Option<usize>,
+}
+
+impl Multipart {
+    /// Limits every field that is read afterward to `bytes`.
+    pub fn field_limit(mut self, bytes: usize) -> Self {
+        self.field_limit = Some(bytes);
+        self
+    }
+
+    /// The next field of the body, or `None` after the last one.
+    ///
+    /// Whatever is left of the previous field is skipped.
+    pub async fn next_field(&mut self) -> Result<Option<Field>, MultipartError> {
+        let field = self
+            .inner
+            .next_field()
+            .await
+            .map_err(MultipartError::from)?;
+
+        Ok(field.map(|inner| Field {
+            inner,
+            limit: self.field_limit,
+            read: 0,
+        }))
+    }
+
+    /// Deserializes the text fields of the body into `T`, like a urlencoded form.
+    ///
+    /// Fields with a filename are skipped, so this is meant for the small fields that accompany
+    /// an upload, not the upload itself.
+    pub async fn collect<T>(mut self) -> Result<T, MultipartError>
+    where
+        T: DeserializeOwned,
+    {
+        let mut pairs = Vec::new();
+
+        while let Some(field) = self.next_field().await? {
+            if field.file_name().is_some() {
+                continue;
+            }
+
+            let name = field.name().unwrap_or_default().to_string();
+            pairs.push((name, field.text().await?));
+        }
+
+        let encoded = serde_urlencoded::to_string(&pairs)
+            .map_err(|e| MultipartError::Deserialize(e.to_string()))?;
+
+        serde_urlencoded::from_str(&encoded)
+            .map_err(|e| MultipartError::Deserialize(e.to_string()))
+    }
+}
+
+impl Consume for Multipart {
+    type Error = MultipartError;
+
+    #[allow(clippy::manual_async_fn)]
+    fn consume(request: Request) -> impl Future<Output=Result<Self, Self::Error>> + Send + 'static {
+        async move {
+            let boundary = request
+                .headers()
+                .get(CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| multer::parse_boundary(value).ok())
+                .ok_or(MultipartError::UnsupportedMediaType)?;
+
+            let limit = body_limit(&request).map_err(MultipartError::Body)?;
+
+            let constraints = Constraints::new()
+                .size_limit(SizeLimit::new().whole_stream(limit as u64));
+
+            let stream = request.into_body().into_data_stream();
+
+            Ok(Multipart {
+                inner: multer::Multipart::with_constraints(stream, boundary, constraints),
+                field_limit: None,
+            })
+        }
+    }
+}
+
+/// A single field of a [`Multipart`] body, which streams its content.
+pub struct Field {
+    inner: multer::Field<'static>,
+    limit: Option<usize>,
+    read: usize,
+}
+
+impl Field {
+    /// The name of the field, from its `Content-Disposition`.
+    pub fn name(&self) -> Option<&str> {
+        self.inner.name()
+    }
+
+    /// The name of the uploaded file, from its `Content-Disposition`.
+    pub fn file_name(&self) -> Option<&str> {
+        self.inner.file_name()
+    }
+
+    /// The `Content-Type` of the field.
+    pub fn content_type(&self) -> Option<&str> {
+        self.inner.content_type().map(|mime| mime.as_ref())
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        self.inner.headers()
+    }
+
+    /// The next chunk of the content, or `None` at the end of the field.
+    pub async fn chunk(&mut self) -> Result<Option<Bytes>, MultipartError> {
+        let chunk = self.inner.chunk().await.map_err(MultipartError::from)?;
+
+        match chunk {
+            Some(chunk) => self.count(chunk).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Reads the whole content of the field.
+    pub async fn bytes(mut self) -> Result<Bytes, MultipartError> {
+        let mut bytes = Vec::new();
+
+        while let Some(chunk) = self.chunk().await? {
+            bytes.extend_from_slice(&chunk);
+        }
+
+        Ok(Bytes::from(bytes))
+    }
+
+    /// Reads the whole content of the field as UTF-8 text.
+    pub async fn text(self) -> Result<String, MultipartError> {
+        let bytes = self.bytes().await?;
+
+        String::from_utf8(bytes.to_vec()).map_err(|_| MultipartError::Body(BodyError::InvalidUtf8))
+    }
+
+    fn count(&mut self, chunk: Bytes) -> Result<Bytes, MultipartError> {
+        self.read += chunk.len();
+
+        match self.limit {
+            Some(limit) if self.read > limit => Err(MultipartError::FieldTooLarge {
+                name: self.name().map(str::to_string),
+                limit,
+            }),
+            _ => Ok(chunk),
+        }
+    }
+}
+
+impl Stream for Field {
+    type Item = Result<Bytes, MultipartError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        match Pin::new(&mut this.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => Poll::Ready(Some(this.count(chunk))),
+            Poll::Ready(Some(Err(err))) => Poll::Ready(Some(Err(MultipartError::from(err)))),
+            Poll::Ready(None) => Poll::Ready(None),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum MultipartError {
+    /// The body could not be read.
+    Body(BodyError),
+    /// The request has no `multipart/form-data` content type with a boundary.
+    UnsupportedMediaType,
+    /// A field is longer than [`Multipart::field_limit`].
+    FieldTooLarge { name: Option<String>, limit: usize },
+    /// The body is not valid `multipart/form-data`.
+    Malformed(String),
+    /// The text fields could not be deserialized into the expected type.
+    Deserialize(String),
+}
+
+impl From<multer::Error> for MultipartError {
+    fn from(err: multer::Error) -> Self {
+        match err {
+            multer::Error::StreamSizeExceeded { limit } => MultipartError::Body(BodyError::TooLarge {
+                limit: limit as usize,
+            }),
+            multer::Error::FieldSizeExceeded { limit, field_name } => MultipartError::FieldTooLarge {
+                name: field_name,
+                limit: limit as usize,
+            },
+            multer::Error::StreamReadFailed(err) => MultipartError::Body(BodyError::Read(err.to_string())),
+            err => MultipartError::Malformed(err.to_string()),
+        }
+    }
+}
+
+impl Display for MultipartError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MultipartError::Body(err) => write!(f, "{err}"),
+            MultipartError::UnsupportedMediaType => write!(f, "Expected content type `multipart/form-data`"),
+            MultipartError::FieldTooLarge { name: Some(name), limit } => {
+                write!(f, "Field `{name}` is larger than {limit} bytes")
+            }
+            MultipartError::FieldTooLarge { name: None, limit } => {
+                write!(f, "Field is larger than {limit} bytes")
+            }
+            MultipartError::Malformed(message) => write!(f, "{message}"),
+            MultipartError::Deserialize(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl IntoResponse for MultipartError {
+    fn into_response(self) -> Response {
+        let status = match self {
+            MultipartError::Body(err) => return err.into_response(),
+            MultipartError::UnsupportedMediaType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            MultipartError::FieldTooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            MultipartError::Malformed(_) => StatusCode::BAD_REQUEST,
+            MultipartError::Deserialize(_) => StatusCode::UNPROCESSABLE_ENTITY,
+        };
+
+        text_response(status, self.to_string())
+    }
+}
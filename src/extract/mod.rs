@@ -1,5 +1,9 @@
 mod path;
 mod query;
+mod state;
 
 pub use path::Path;
+pub(crate) use path::PathParams;
 pub use query::Query;
+pub use state::FromRef;
+pub use state::State;
@@ -1,29 +1,37 @@
 use serde::Deserializer;
-use serde::de::{DeserializeOwned, MapAccess, Visitor, value};
-use std::collections::HashMap;
+use serde::de::{DeserializeOwned, DeserializeSeed, Error, MapAccess, SeqAccess, Visitor, value};
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
+use http::StatusCode;
 use crate::{Request, Response};
-use crate::body::Json;
 use crate::request::Extract;
-use crate::response::IntoResponse;
+use crate::response::{text_response, IntoResponse};
 
+/// The parameters of the matched route, e.g. `Path<u32>` for `/users/{id}`, or
+/// `Path<(String, u32)>` or a struct for `/teams/{team}/members/{id}`.
+///
+/// Tuples take the parameters in the order they appear in the route; structs take them by name.
 pub struct Path<T>(pub T);
 
+/// The parameters the router captured from the path, in the order they appear in the route.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct PathParams(pub(crate) Vec<(String, String)>);
+
 impl<S, T> Extract<S> for Path<T>
 where
     T: DeserializeOwned
 {
     type Error = PathError;
 
-    fn extract(request: &Request, _: &S) -> impl Future<Output=Result<Self, Self::Error>> {
+    #[allow(clippy::manual_async_fn)]
+    fn extract(request: &Request, _: &S) -> impl Future<Output=Result<Self, Self::Error>> + Send {
         async {
             let params = request
                 .extensions()
-                .get::<HashMap<String, String>>()
+                .get::<PathParams>()
                 .ok_or_else(|| PathError("No parameters in the request.".to_string()))?;
 
-            let path = T::deserialize(PathDeserializer(params))
+            let path = T::deserialize(PathDeserializer(&params.0))
                 .map_err(|e| PathError(e.to_string()))?;
 
             Ok(Path(path))
@@ -47,7 +55,33 @@ impl Display for PathError {
     }
 }
 
-struct PathDeserializer<'a>(&'a HashMap<String, String>);
+struct PathDeserializer<'a>(&'a [(String, String)]);
+
+impl<'a> PathDeserializer<'a> {
+    /// The only parameter, for paths deserialized into a single value like `u32`.
+    fn single(&self) -> Result<PathValueDeserializer<'a>, value::Error> {
+        match self.0 {
+            [(_, value)] => Ok(PathValueDeserializer(value)),
+            params => Err(value::Error::custom(format!(
+                "expected 1 path parameter, found {}",
+                params.len()
+            ))),
+        }
+    }
+}
+
+macro_rules! single_value {
+    ($($method:ident)*) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                self.single()?.$method(visitor)
+            }
+        )*
+    };
+}
 
 impl<'de, 'a> Deserializer<'de> for PathDeserializer<'a> {
     type Error = value::Error;
@@ -62,15 +96,80 @@ impl<'de, 'a> Deserializer<'de> for PathDeserializer<'a> {
         })
     }
 
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(PathSeqAccess(self.0.iter()))
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        if len != self.0.len() {
+            return Err(value::Error::invalid_length(self.0.len(), &visitor));
+        }
+
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(self, _: &'static str, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.single()?.deserialize_enum(name, variants, visitor)
+    }
+
+    single_value! {
+        deserialize_bool deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_f32
+        deserialize_f64 deserialize_char deserialize_str deserialize_string deserialize_option
+        deserialize_bytes deserialize_byte_buf
+    }
+
     serde::forward_to_deserialize_any! {
-        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string
-        seq tuple tuple_struct map struct enum identifier ignored_any option unit
-        bytes byte_buf unit_struct newtype_struct
+        map struct identifier ignored_any unit unit_struct
+    }
+}
+
+struct PathSeqAccess<'a>(std::slice::Iter<'a, (String, String)>);
+
+impl<'de, 'a> SeqAccess<'de> for PathSeqAccess<'a> {
+    type Error = value::Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Self::Error>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        self.0
+            .next()
+            .map(|(_, value)| seed.deserialize(PathValueDeserializer(value)))
+            .transpose()
     }
 }
 
 struct PathMapAccess<'a> {
-    iter: std::collections::hash_map::Iter<'a, String, String>,
+    iter: std::slice::Iter<'a, (String, String)>,
     value_opt: Option<&'a String>,
 }
 
@@ -96,7 +195,88 @@ impl<'de, 'a> MapAccess<'de> for PathMapAccess<'a> {
         V: serde::de::DeserializeSeed<'de>,
     {
         let v = self.value_opt.take().expect("value missing for key");
-        let val_de = value::StrDeserializer::new(v);
-        seed.deserialize(val_de)
+        seed.deserialize(PathValueDeserializer(v))
+    }
+}
+
+struct PathValueDeserializer<'a>(&'a str);
+
+macro_rules! parse_value {
+    ($($method:ident => $visit:ident),* $(,)?) => {
+        $(
+            fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+            where
+                V: Visitor<'de>,
+            {
+                let value = self.0.parse().map_err(|_| {
+                    value::Error::custom(format!("cannot parse `{}` from path parameter", self.0))
+                })?;
+
+                visitor.$visit(value)
+            }
+        )*
+    };
+}
+
+impl<'de, 'a> Deserializer<'de> for PathValueDeserializer<'a> {
+    type Error = value::Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(self.0)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(self, _: &'static str, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(value::StrDeserializer::<value::Error>::new(self.0))
+    }
+
+    parse_value! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    serde::forward_to_deserialize_any! {
+        str string seq tuple tuple_struct map struct identifier ignored_any unit
+        bytes byte_buf unit_struct
+    }
+}
+
+impl IntoResponse for PathError {
+    fn into_response(self) -> Response {
+        text_response(StatusCode::BAD_REQUEST, self.0)
     }
 }
@@ -1,9 +1,10 @@
 use std::fmt::{Display, Formatter};
 use std::ops::Deref;
+use http::StatusCode;
 use serde::de::DeserializeOwned;
-use crate::body::Empty;
-use crate::Request;
+use crate::{Request, Response};
 use crate::request::Extract;
+use crate::response::{text_response, IntoResponse};
 
 pub struct Query<T>(pub T);
 
@@ -13,7 +14,8 @@ where
 {
     type Error = QueryError;
 
-    fn extract(request: &Request, _: &S) -> impl Future<Output=Result<Self, Self::Error>> {
+    #[allow(clippy::manual_async_fn)]
+    fn extract(request: &Request, _: &S) -> impl Future<Output=Result<Self, Self::Error>> + Send {
         async {
             let query = request.uri().query().unwrap_or_default();
 
@@ -42,3 +44,9 @@ impl Display for QueryError {
         write!(f, "{}", self.0)
     }
 }
+
+impl IntoResponse for QueryError {
+    fn into_response(self) -> Response {
+        text_response(StatusCode::BAD_REQUEST, self.0)
+    }
+}
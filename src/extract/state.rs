@@ -0,0 +1,46 @@
+use std::ops::Deref;
+use crate::body::Empty;
+use crate::Request;
+use crate::request::Extract;
+
+/// Extracts a value of type `T` from the application state.
+///
+/// `T` can be the whole state, or any part of it that implements [`FromRef`].
+pub struct State<T>(pub T);
+
+/// Projects a value out of a larger state, so handlers can ask for only the part they need.
+///
+/// Every `Clone` type can be projected out of itself.
+pub trait FromRef<S> {
+    fn from_ref(state: &S) -> Self;
+}
+
+impl<T> FromRef<T> for T
+where
+    T: Clone,
+{
+    fn from_ref(state: &T) -> Self {
+        state.clone()
+    }
+}
+
+impl<S, T> Extract<S> for State<T>
+where
+    T: FromRef<S> + Send,
+{
+    type Error = Empty;
+
+    #[allow(clippy::manual_async_fn)]
+    fn extract(_: &Request, state: &S) -> impl Future<Output=Result<Self, Self::Error>> + Send {
+        let state = T::from_ref(state);
+        async move { Ok(State(state)) }
+    }
+}
+
+impl<T> Deref for State<T> {
+    type Target = T;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
@@ -1,8 +1,9 @@
 use std::pin::Pin;
 use std::sync::Arc;
-use http_body_util::{BodyExt, Full};
+use http_body_util::BodyExt;
 use crate::{Request, Response};
-use crate::body::Empty;
+use crate::body::{Body, Empty};
+use crate::request::{Consume, Extract};
 use crate::response::IntoResponse;
 
 pub trait Layer<H>
@@ -33,6 +34,72 @@ where
     }
 }
 
+/// A handler that can be registered on a [`Router`](crate::Router).
+///
+/// Implemented for async functions taking any number of [`Extract`] arguments, followed by a
+/// single [`Consume`] argument and the shared state, e.g.
+/// `fn(Path<Id>, Query<Filter>, Json<Body>, Arc<S>)`.
+/// The extractors run in order against the request before it is consumed, and the first rejection
+/// is returned as the response. `T` is the tuple of argument types and only serves to keep the
+/// implementations apart.
+pub trait RouteHandler<T, S>: Clone + Send + Sync + 'static {
+    fn call(&self, request: Request, state: Arc<S>) -> Pin<Box<dyn Future<Output=Response> + Send>>;
+}
+
+macro_rules! impl_route_handler {
+    ($($extract:ident),*) => {
+        impl<F, Fut, O, S, $($extract,)* C> RouteHandler<($($extract,)* C,), S> for F
+        where
+            F: Fn($($extract,)* C, Arc<S>) -> Fut + Clone + Send + Sync + 'static,
+            Fut: Future<Output=O> + Send + 'static,
+            O: IntoResponse + 'static,
+            S: Send + Sync + 'static,
+            $($extract: Extract<S> + Send + 'static,)*
+            C: Consume + Send + 'static,
+        {
+            #[allow(non_snake_case)]
+            fn call(&self, request: Request, state: Arc<S>) -> Pin<Box<dyn Future<Output=Response> + Send>> {
+                let handler = self.clone();
+
+                Box::pin(async move {
+                    $(
+                        let $extract = match <$extract as Extract<S>>::extract(&request, state.as_ref()).await {
+                            Ok(value) => value,
+                            Err(rejection) => return rejection.into_response(),
+                        };
+                    )*
+
+                    let input = match C::consume(request).await {
+                        Ok(input) => input,
+                        Err(rejection) => return rejection.into_response(),
+                    };
+
+                    handler($($extract,)* input, state)
+                        .await
+                        .into_response()
+                })
+            }
+        }
+    };
+}
+
+impl_route_handler!();
+impl_route_handler!(E1);
+impl_route_handler!(E1, E2);
+impl_route_handler!(E1, E2, E3);
+impl_route_handler!(E1, E2, E3, E4);
+impl_route_handler!(E1, E2, E3, E4, E5);
+impl_route_handler!(E1, E2, E3, E4, E5, E6);
+impl_route_handler!(E1, E2, E3, E4, E5, E6, E7);
+impl_route_handler!(E1, E2, E3, E4, E5, E6, E7, E8);
+impl_route_handler!(E1, E2, E3, E4, E5, E6, E7, E8, E9);
+impl_route_handler!(E1, E2, E3, E4, E5, E6, E7, E8, E9, E10);
+impl_route_handler!(E1, E2, E3, E4, E5, E6, E7, E8, E9, E10, E11);
+impl_route_handler!(E1, E2, E3, E4, E5, E6, E7, E8, E9, E10, E11, E12);
+impl_route_handler!(E1, E2, E3, E4, E5, E6, E7, E8, E9, E10, E11, E12, E13);
+impl_route_handler!(E1, E2, E3, E4, E5, E6, E7, E8, E9, E10, E11, E12, E13, E14);
+impl_route_handler!(E1, E2, E3, E4, E5, E6, E7, E8, E9, E10, E11, E12, E13, E14, E15);
+
 pub struct Boxed<H>(Arc<H>);
 
 impl<H> Boxed<H> {
@@ -75,7 +142,7 @@ impl<S> Handler<Request, S> for EchoHandler {
                 return Empty.into_response()
             };
 
-            Response::new(Full::new(body.to_bytes()))
+            Response::new(Body::from(body.to_bytes()))
         })
     }
 }
\ No newline at end of file
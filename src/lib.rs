@@ -5,13 +5,20 @@ pub mod body;
 pub mod request;
 pub mod response;
 pub mod extract;
+pub mod listener;
+pub mod server;
+pub mod test;
+pub mod tls;
 
 pub use app::App;
+pub use app::ShutdownSummary;
 pub use handler::Handler;
 pub use handler::Layer;
+pub use handler::RouteHandler;
 pub use response::Response;
 pub use request::Request;
 pub use router::Router;
+pub use server::ServerConfig;
 
 pub use http::StatusCode;
 
@@ -23,6 +30,7 @@ pub mod prelude {
     pub use crate::request::Request;
     pub use crate::router::Router;
     pub use crate::body::Json;
+    pub use crate::body::Form;
     pub use crate::body::Empty;
     pub use crate::StatusCode;
 }
@@ -0,0 +1,55 @@
+use std::io;
+use std::net::SocketAddr;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use tracing::debug;
+#[cfg(unix)]
+use tokio::net::{UnixListener, UnixStream};
+#[cfg(unix)]
+pub use tokio::net::unix::UCred;
+
+/// A source of connections for [`App::listen`](crate::App::listen).
+///
+/// The address of each connection is inserted into the extensions of every request made on it.
+pub trait Listener: Send + 'static {
+    type Io: AsyncRead + AsyncWrite + Unpin + Send + 'static;
+    type Addr: Clone + Send + Sync + 'static;
+
+    fn accept(&mut self) -> impl Future<Output=io::Result<(Self::Io, Self::Addr)>> + Send;
+}
+
+impl Listener for TcpListener {
+    type Io = TcpStream;
+    type Addr = SocketAddr;
+
+    #[allow(clippy::manual_async_fn)]
+    fn accept(&mut self) -> impl Future<Output=io::Result<(Self::Io, Self::Addr)>> + Send {
+        async move { TcpListener::accept(self).await }
+    }
+}
+
+/// Accepts connections on a Unix domain socket.
+///
+/// Instead of a `SocketAddr`, requests carry the [`UCred`] of the peer process. Connections whose
+/// credentials cannot be read are closed.
+#[cfg(unix)]
+impl Listener for UnixListener {
+    type Io = UnixStream;
+    type Addr = UCred;
+
+    #[allow(clippy::manual_async_fn)]
+    fn accept(&mut self) -> impl Future<Output=io::Result<(Self::Io, Self::Addr)>> + Send {
+        async move {
+            loop {
+                let (stream, _) = UnixListener::accept(self).await?;
+
+                // Only this connection is affected, so drop it rather than fail the listener.
+                match stream.peer_cred() {
+                    Ok(credentials) => return Ok((stream, credentials)),
+                    Err(err) => debug!("Could not read peer credentials: {:?}", err),
+                }
+            }
+        }
+    }
+}
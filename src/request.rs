@@ -1,13 +1,11 @@
-use std::fmt::Display;
 use std::future::Future;
-use hyper::body::Incoming;
-use crate::body::Empty;
+use crate::body::{Body, Empty};
 use crate::response::IntoResponse;
 
-pub type Request<B = Incoming> = http::Request<B>;
+pub type Request<B = Body> = http::Request<B>;
 
 pub trait Consume: Sized {
-    type Error: Display + Send + Sync + 'static;
+    type Error: IntoResponse + Send + 'static;
 
     fn consume(request: Request) -> impl Future<Output=Result<Self, Self::Error>> + Send + 'static;
 }
@@ -22,10 +20,10 @@ impl Consume for Request {
 }
 
 pub trait Extract<S>: Sized {
-    type Error: Display + Send + Sync + 'static;
+    type Error: IntoResponse + Send + 'static;
 
     fn extract(
         request: &Request,
         state: &S,
-    ) -> impl Future<Output=Result<Self, Self::Error>>;
+    ) -> impl Future<Output=Result<Self, Self::Error>> + Send;
 }
@@ -1,8 +1,12 @@
-use http::StatusCode;
-use http_body_util::Full;
+use crate::body::Body;
+use http::header::{HeaderName, CONTENT_TYPE};
+use http::{HeaderMap, HeaderValue, StatusCode};
 use hyper::body::Bytes;
 
-pub type Response<B = Full<Bytes>> = http::Response<B>;
+const TEXT_PLAIN_UTF_8: &str = "text/plain; charset=utf-8";
+const OCTET_STREAM: &str = "application/octet-stream";
+
+pub type Response<B = Body> = http::Response<B>;
 
 pub trait IntoResponse {
     fn into_response(self) -> Response;
@@ -27,3 +31,127 @@ where
     }
 }
 
+
+/// An empty response with this status.
+impl IntoResponse for StatusCode {
+    fn into_response(self) -> Response {
+        let mut response = Response::new(Body::empty());
+        *response.status_mut() = self;
+        response
+    }
+}
+
+/// An empty `200 OK` response.
+impl IntoResponse for () {
+    fn into_response(self) -> Response {
+        Response::new(Body::empty())
+    }
+}
+
+impl IntoResponse for String {
+    fn into_response(self) -> Response {
+        with_content_type(Body::from(self), TEXT_PLAIN_UTF_8)
+    }
+}
+
+impl IntoResponse for &'static str {
+    fn into_response(self) -> Response {
+        with_content_type(Body::from(self), TEXT_PLAIN_UTF_8)
+    }
+}
+
+impl IntoResponse for Bytes {
+    fn into_response(self) -> Response {
+        with_content_type(Body::from(self), OCTET_STREAM)
+    }
+}
+
+impl IntoResponse for Vec<u8> {
+    fn into_response(self) -> Response {
+        with_content_type(Body::from(self), OCTET_STREAM)
+    }
+}
+
+/// The response of `T`, or an empty `404 Not Found` for `None`.
+impl<T> IntoResponse for Option<T>
+where
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        match self {
+            Some(some) => some.into_response(),
+            None => StatusCode::NOT_FOUND.into_response(),
+        }
+    }
+}
+
+/// An empty `200 OK` response with these headers.
+impl IntoResponse for HeaderMap {
+    fn into_response(self) -> Response {
+        let mut response = Response::new(Body::empty());
+        *response.headers_mut() = self;
+        response
+    }
+}
+
+/// The response of `T` with its status replaced.
+impl<T> IntoResponse for (StatusCode, T)
+where
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let (status, inner) = self;
+
+        let mut response = inner.into_response();
+        *response.status_mut() = status;
+        response
+    }
+}
+
+/// The response of `T` with its status replaced, and these headers replacing those of the
+/// same name.
+impl<T> IntoResponse for (StatusCode, HeaderMap, T)
+where
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let (status, headers, inner) = self;
+
+        let mut response = inner.into_response();
+        *response.status_mut() = status;
+        response.headers_mut().extend(headers);
+        response
+    }
+}
+
+/// The response of `T` with these headers replacing those of the same name, e.g. to set a
+/// different `Content-Type`.
+impl<T, const N: usize> IntoResponse for ([(HeaderName, HeaderValue); N], T)
+where
+    T: IntoResponse,
+{
+    fn into_response(self) -> Response {
+        let (headers, inner) = self;
+
+        let mut response = inner.into_response();
+        response.headers_mut().extend(HeaderMap::from_iter(headers));
+        response
+    }
+}
+
+/// A `text/plain` response with `status`, e.g. for the rejections of extractors and consumers.
+pub(crate) fn text_response(status: StatusCode, text: String) -> Response {
+    let mut response = text.into_response();
+    *response.status_mut() = status;
+    response
+}
+
+fn with_content_type(body: Body, content_type: &'static str) -> Response {
+    let mut response = Response::new(body);
+
+    response
+        .headers_mut()
+        .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+
+    response
+}
@@ -1,25 +1,61 @@
-use crate::request::Consume;
-use crate::response::IntoResponse;
+use crate::handler::RouteHandler;
 use crate::{Handler, Request, Response};
-use http::{Method, StatusCode};
-use http_body_util::Full;
-use hyper::body::Bytes;
+use http::header::{ALLOW, CONTENT_LENGTH};
+use http::{HeaderValue, Method, StatusCode};
+use hyper::body::Body as _;
+use crate::body::{Body, BodyLimit};
+use crate::extract::PathParams;
 use matchit::Match;
 use std::collections::HashMap;
 use std::pin::Pin;
 use std::sync::Arc;
-use crate::body::Empty;
 
 #[derive(Clone, Eq, PartialEq, Hash, Default)]
 struct RouteId(u32);
 
-type MethodHandler<S> = dyn Fn(Request, S) -> Pin<Box<dyn Future<Output=Response> + Send + 'static>>
+type MethodHandler<S> = dyn Fn(Request, Arc<S>) -> Pin<Box<dyn Future<Output=Response> + Send + 'static>>
 + Send
 + Sync
 + 'static;
 
 struct Endpoint<S> {
     methods: HashMap<Method, Arc<MethodHandler<S>>>,
+    any: Option<Arc<MethodHandler<S>>>,
+}
+
+impl<S> Endpoint<S> {
+    /// Finds the handler for `method`, letting `HEAD` fall through to `GET`.
+    fn handler(&self, method: &Method) -> Option<&Arc<MethodHandler<S>>> {
+        self.methods
+            .get(method)
+            .or_else(|| match *method {
+                Method::HEAD => self.methods.get(&Method::GET),
+                _ => None,
+            })
+            .or(self.any.as_ref())
+    }
+
+    /// The value of the `Allow` header for this endpoint, which always includes `OPTIONS`, and
+    /// `HEAD` when there is a `GET` handler.
+    fn allow(&self) -> HeaderValue {
+        let head = self
+            .methods
+            .contains_key(&Method::GET)
+            .then_some(&Method::HEAD);
+
+        let mut methods = self
+            .methods
+            .keys()
+            .chain([&Method::OPTIONS])
+            .chain(head)
+            .map(Method::as_str)
+            .collect::<Vec<_>>();
+
+        methods.sort_unstable();
+        methods.dedup();
+
+        HeaderValue::from_str(&methods.join(", ")).expect("Method names are valid header values")
+    }
 }
 
 #[derive(Default)]
@@ -28,19 +64,25 @@ pub struct Router<S>
     inner: matchit::Router<RouteId>,
     routes: HashMap<RouteId, Endpoint<S>>,
     next_id: RouteId,
-    path_to_id: HashMap<String, RouteId>
+    path_to_id: HashMap<String, RouteId>,
+    fallback: Option<Arc<MethodHandler<S>>>,
+    method_not_allowed_fallback: Option<Arc<MethodHandler<S>>>,
+    body_limit: Option<BodyLimit>,
 }
 
 impl<S> Router<S>
 where
-    S: Clone + Send + Sync + 'static,
+    S: Send + Sync + 'static,
 {
     pub fn new() -> Self {
         Self {
             inner: matchit::Router::new(),
             routes: HashMap::new(),
             next_id: RouteId(0),
-            path_to_id: HashMap::new()
+            path_to_id: HashMap::new(),
+            fallback: None,
+            method_not_allowed_fallback: None,
+            body_limit: None,
         }
     }
 
@@ -50,77 +92,156 @@ where
         id
     }
 
-    pub fn post<I, O>(
-        self,
-        path: &str,
-        handler: impl Handler<I, S, Output=O> + Clone + Send + Sync + 'static,
-    ) -> Self
-    where
-        I: Consume + Send + 'static,
-        O: IntoResponse + 'static,
-    {
+    pub fn post<T>(self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
         self.insert(path, Method::POST, handler)
     }
 
-    pub fn put<I, O>(
-        self,
-        path: &str,
-        handler: impl Handler<I, S, Output=O> + Clone + Send + Sync + 'static,
-    ) -> Self
-    where
-        I: Consume + Send + 'static,
-        O: IntoResponse + 'static,
-    {
+    pub fn put<T>(self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
         self.insert(path, Method::PUT, handler)
     }
 
-    pub fn get<I, O>(
-        self,
-        path: &str,
-        handler: impl Handler<I, S, Output=O> + Clone + Send + Sync + 'static,
-    ) -> Self
-    where
-        I: Consume + Send + 'static,
-        O: IntoResponse + 'static,
-    {
+    pub fn get<T>(self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
         self.insert(path, Method::GET, handler)
     }
 
-    pub fn patch<I, O>(
-        self,
-        path: &str,
-        handler: impl Handler<I, S, Output=O> + Clone + Send + Sync + 'static,
-    ) -> Self
-    where
-        I: Consume + Send + 'static,
-        O: IntoResponse + 'static,
-    {
+    pub fn patch<T>(self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
         self.insert(path, Method::PATCH, handler)
     }
 
 
-    pub fn delete<I, O>(
-        self,
-        path: &str,
-        handler: impl Handler<I, S, Output=O> + Clone + Send + Sync + 'static,
-    ) -> Self
-    where
-        I: Consume + Send + 'static,
-        O: IntoResponse + 'static,
-    {
+    pub fn delete<T>(self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
         self.insert(path, Method::DELETE, handler)
     }
 
-    pub fn insert<I, O>(
-        mut self,
-        path: &str,
-        method: Method,
-        handler: impl Handler<I, S, Output=O> + Clone + Send + Sync + 'static,
-    ) -> Self
-    where
-        I: Consume + Send + 'static,
-        O: IntoResponse + 'static,
-    {
+    /// Registers a `HEAD` handler. Without one, `HEAD` requests are answered by the `GET` handler.
+    pub fn head<T>(self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
+        self.insert(path, Method::HEAD, handler)
+    }
+
+    /// Registers an `OPTIONS` handler. Without one, `OPTIONS` requests are answered with the
+    /// allowed methods.
+    pub fn options<T>(self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
+        self.insert(path, Method::OPTIONS, handler)
+    }
+
+    pub fn trace<T>(self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
+        self.insert(path, Method::TRACE, handler)
+    }
+
+    pub fn connect<T>(self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
+        self.insert(path, Method::CONNECT, handler)
+    }
+
+    /// Registers a handler for every method that has no handler of its own on `path`.
+    pub fn any<T>(mut self, path: &str, handler: impl RouteHandler<T, S>) -> Self {
+        self.insert_any_handler(path, Self::method_handler(handler));
+        self
+    }
+
+    pub fn insert<T>(mut self, path: &str, method: Method, handler: impl RouteHandler<T, S>) -> Self {
+        self.insert_method_handler(path, method, Self::method_handler(handler));
+        self
+    }
+
+    /// Sets the handler for requests whose path matches no route, instead of an empty 404.
+    pub fn fallback<T>(mut self, handler: impl RouteHandler<T, S>) -> Self {
+        self.fallback = Some(Self::method_handler(handler));
+        self
+    }
+
+    /// Sets the handler for requests whose path matches a route that has no handler for the
+    /// request method, instead of an empty 405.
+    pub fn method_not_allowed_fallback<T>(mut self, handler: impl RouteHandler<T, S>) -> Self {
+        self.method_not_allowed_fallback = Some(Self::method_handler(handler));
+        self
+    }
+
+    /// Limits the request bodies read by the routes and fallbacks of this router to `bytes`.
+    ///
+    /// This replaces a limit set on the app, and is kept when the router is nested or merged.
+    /// Routes wrapped in their own [`BodyLimit`] use that limit instead.
+    pub fn body_limit(mut self, bytes: usize) -> Self {
+        self.body_limit = Some(BodyLimit(bytes));
+        self
+    }
+
+    /// Mounts every route of `router` under `prefix`.
+    ///
+    /// Parameters in the prefix are matched like any other part of the path, so
+    /// `nest("/teams/{team}", router)` makes `team` available to the nested handlers' `Path`.
+    /// The fallbacks of `router` are not used; unmatched requests go to this router's fallbacks.
+    pub fn nest(mut self, prefix: &str, router: Router<S>) -> Self {
+        let prefix = prefix.trim_end_matches('/');
+
+        if !prefix.is_empty() && !prefix.starts_with('/') {
+            panic!("Nesting prefix `{prefix}` must start with `/`");
+        }
+
+        let body_limit = router.body_limit;
+
+        for (path, endpoint) in router.into_endpoints() {
+            let path = match path.as_str() {
+                "/" if !prefix.is_empty() => prefix.to_string(),
+                _ => format!("{prefix}{path}"),
+            };
+
+            for (method, handler) in endpoint.methods {
+                self.insert_method_handler(&path, method, Self::limited(handler, body_limit));
+            }
+
+            if let Some(handler) = endpoint.any {
+                self.insert_any_handler(&path, Self::limited(handler, body_limit));
+            }
+        }
+
+        self
+    }
+
+    /// Adds every route of `router` to this one, keeping their paths as they are.
+    ///
+    /// Fallbacks set on `router` are taken over, unless this router already has its own.
+    pub fn merge(mut self, mut router: Router<S>) -> Self {
+        self.fallback = self.fallback.or(router.fallback.take());
+        self.method_not_allowed_fallback = self
+            .method_not_allowed_fallback
+            .or(router.method_not_allowed_fallback.take());
+
+        self.nest("", router)
+    }
+
+    fn method_handler<T>(handler: impl RouteHandler<T, S>) -> Arc<MethodHandler<S>> {
+        Arc::new(move |request, state| handler.call(request, state))
+    }
+
+    /// Makes `handler` apply `limit` to its requests, so a nested router keeps its own limit.
+    fn limited(handler: Arc<MethodHandler<S>>, limit: Option<BodyLimit>) -> Arc<MethodHandler<S>> {
+        match limit {
+            Some(limit) => Arc::new(move |mut request: Request, state| {
+                request.extensions_mut().insert(limit);
+                handler(request, state)
+            }),
+            None => handler,
+        }
+    }
+
+    fn insert_method_handler(&mut self, path: &str, method: Method, handler: Arc<MethodHandler<S>>) {
+        if self
+            .endpoint_mut(path)
+            .methods
+            .insert(method.clone(), handler)
+            .is_some()
+        {
+            panic!("Route `{path}` already has handler for method `{method}`");
+        }
+    }
+
+    fn insert_any_handler(&mut self, path: &str, handler: Arc<MethodHandler<S>>) {
+        if self.endpoint_mut(path).any.replace(handler).is_some() {
+            panic!("Route `{path}` already has handler for any method");
+        }
+    }
+
+    fn endpoint_mut(&mut self, path: &str) -> &mut Endpoint<S> {
         let id = match self.path_to_id.get(path) {
             Some(existing_id) => existing_id.clone(),
             None => {
@@ -131,85 +252,109 @@ where
             }
         };
 
-        let method_handler: Arc<MethodHandler<S>> = Arc::new(move |request, state| {
-            let handler = Arc::new(handler.clone());
-            Box::pin(async move {
-                match I::consume(request).await {
-                    Ok(input) => handler
-                        .handle(input, state)
-                        .await
-                        .into_response(),
-                    Err(_) => {
-                        let mut response = Empty.into_response();
-                        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
-                        response
-                    }
-                }
-            })
-        });
-
-        let endpoint = self.routes.entry(id).or_insert_with(|| Endpoint {
+        self.routes.entry(id).or_insert_with(|| Endpoint {
             methods: HashMap::new(),
-        });
-
-        if endpoint
-            .methods
-            .insert(method.clone(), method_handler)
-            .is_some()
-        {
-            panic!("Route `{path}` already has handler for method `{method}`");
-        }
+            any: None,
+        })
+    }
 
-        self
+    fn into_endpoints(mut self) -> impl Iterator<Item=(String, Endpoint<S>)> {
+        self.path_to_id
+            .into_iter()
+            .filter_map(move |(path, id)| Some((path, self.routes.remove(&id)?)))
     }
 }
 
-impl<S> Handler<Request, S> for Router<S>
+impl<S> Handler<Request, Arc<S>> for Router<S>
 where
-    S: Clone + Send + Sync + 'static,
+    S: Send + Sync + 'static,
 {
     type Output = Response;
     type Future = Pin<Box<dyn Future<Output=Self::Output> + Send>>;
 
-    fn handle(&self, mut req: Request, state: S) -> Self::Future {
+    fn handle(&self, mut req: Request, state: Arc<S>) -> Self::Future {
+        if let Some(limit) = self.body_limit {
+            req.extensions_mut().insert(limit);
+        }
+
         let (method, path) = (req.method(), req.uri().path().to_string());
 
-        let result = {
-            let mut response = Response::new(Full::new(Bytes::new()));
-            *response.status_mut() = StatusCode::NOT_FOUND;
-
-            if let Ok(Match {
-              value: route_id,
-              params,
-            }) = self.inner.at(&path) {
-                if let Some(endpoint) = self.routes.get(route_id) {
-                    if let Some(handler) = endpoint.methods.get(method) {
-                        let params = params
-                            .iter()
-                            .map(|(k, v)| (k.to_string(), v.to_string()))
-                            .collect::<HashMap<String, String>>();
-
-                        Ok((handler.clone(), params))
-                    } else {
-                        *response.status_mut() = StatusCode::METHOD_NOT_ALLOWED;
-                        Err(response)
-                    }
+        let result = if let Ok(Match {
+          value: route_id,
+          params,
+        }) = self.inner.at(&path) {
+            if let Some(endpoint) = self.routes.get(route_id) {
+                if let Some(handler) = endpoint.handler(method) {
+                    let params = params
+                        .iter()
+                        .map(|(k, v)| (k.to_string(), v.to_string()))
+                        .collect::<Vec<_>>();
+
+                    Ok((handler.clone(), PathParams(params)))
+                } else if method == Method::OPTIONS {
+                    Err((StatusCode::NO_CONTENT, Some(endpoint.allow())))
                 } else {
-                    Err(response)
+                    Err((StatusCode::METHOD_NOT_ALLOWED, Some(endpoint.allow())))
                 }
             } else {
-                Err(response)
+                Err((StatusCode::NOT_FOUND, None))
             }
+        } else {
+            Err((StatusCode::NOT_FOUND, None))
         };
 
-        Box::pin(async move {
-            match result {
-                Ok((h, params)) => {
-                    req.extensions_mut().insert(params);
-                    h(req, state).await
-                }
-                Err(res) => res,
+        let (handler, params) = match result {
+            Ok(found) => found,
+            Err((status, allow)) => {
+                let fallback = match status {
+                    StatusCode::NO_CONTENT => &None,
+                    StatusCode::METHOD_NOT_ALLOWED => &self.method_not_allowed_fallback,
+                    _ => &self.fallback,
+                };
+
+                let future = match fallback {
+                    Some(handler) => handler(req, state),
+                    None => {
+                        let mut response = Response::new(Body::empty());
+                        *response.status_mut() = status;
+                        Box::pin(async move { response })
+                    }
+                };
+
+                return Box::pin(async move {
+                    let mut response = future.await;
+
+                    if let Some(allow) = allow {
+                        response.headers_mut().entry(ALLOW).or_insert(allow);
+                    }
+
+                    response
+                });
             }
-        })
+        };
+
+        let head = req.method() == Method::HEAD;
+        req.extensions_mut().insert(params);
+        let future = handler(req, state);
+
+        if head {
+            Box::pin(async move { strip_body(future.await) })
+        } else {
+            future
+        }
+    }
+}
+
+/// Removes the body of a response to a `HEAD` request, keeping the `Content-Length` it would
+/// have had.
+fn strip_body(mut response: Response) -> Response {
+    if let Some(length) = response.body().size_hint().exact() {
+        response
+            .headers_mut()
+            .entry(CONTENT_LENGTH)
+            .or_insert(HeaderValue::from(length));
     }
-}
\ No newline at end of file
+
+    *response.body_mut() = Body::empty();
+    response
+}
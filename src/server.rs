@@ -0,0 +1,176 @@
+use hyper_util::rt::{TokioExecutor, TokioTimer};
+use hyper_util::server::conn::auto::Builder;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+
+/// The smallest buffer hyper accepts for reading HTTP/1 request heads.
+const MINIMUM_MAX_HEADER_SIZE: usize = 8192;
+
+/// The HTTP versions a server accepts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Protocol {
+    /// HTTP/1.1 and HTTP/2, detected per connection. The only mode that supports upgrades.
+    #[default]
+    Auto,
+    Http1Only,
+    Http2Only,
+}
+
+/// Settings for the HTTP connections served by an [`App`](crate::App).
+///
+/// Every setting left unset keeps hyper's default.
+#[derive(Debug, Clone, Default)]
+pub struct ServerConfig {
+    protocol: Protocol,
+    header_read_timeout: Option<Duration>,
+    keep_alive: Option<bool>,
+    max_header_size: Option<usize>,
+    max_headers: Option<usize>,
+    max_concurrent_streams: Option<u32>,
+    initial_stream_window_size: Option<u32>,
+    initial_connection_window_size: Option<u32>,
+    keep_alive_interval: Option<Duration>,
+    keep_alive_timeout: Option<Duration>,
+    max_connections: Option<usize>,
+}
+
+impl ServerConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn protocol(mut self, protocol: Protocol) -> Self {
+        self.protocol = protocol;
+        self
+    }
+
+    /// Closes HTTP/1 connections that do not send a complete request head within `timeout`.
+    pub fn header_read_timeout(mut self, timeout: Duration) -> Self {
+        self.header_read_timeout = Some(timeout);
+        self
+    }
+
+    /// Whether HTTP/1 connections are kept open between requests.
+    pub fn keep_alive(mut self, enabled: bool) -> Self {
+        self.keep_alive = Some(enabled);
+        self
+    }
+
+    /// Limits the size of request headers in bytes. HTTP/1 requires at least 8192 bytes.
+    pub fn max_header_size(mut self, bytes: usize) -> Self {
+        if bytes < MINIMUM_MAX_HEADER_SIZE {
+            panic!("Maximum header size must be at least {MINIMUM_MAX_HEADER_SIZE} bytes");
+        }
+
+        self.max_header_size = Some(bytes);
+        self
+    }
+
+    /// Limits the number of headers in an HTTP/1 request.
+    pub fn max_headers(mut self, count: usize) -> Self {
+        self.max_headers = Some(count);
+        self
+    }
+
+    /// Limits the number of concurrent streams on an HTTP/2 connection.
+    pub fn max_concurrent_streams(mut self, streams: u32) -> Self {
+        self.max_concurrent_streams = Some(streams);
+        self
+    }
+
+    /// Sets the initial HTTP/2 flow control window of each stream, in bytes.
+    pub fn initial_stream_window_size(mut self, bytes: u32) -> Self {
+        self.initial_stream_window_size = Some(bytes);
+        self
+    }
+
+    /// Sets the initial HTTP/2 flow control window of each connection, in bytes.
+    pub fn initial_connection_window_size(mut self, bytes: u32) -> Self {
+        self.initial_connection_window_size = Some(bytes);
+        self
+    }
+
+    /// Sends an HTTP/2 ping every `interval`, and closes the connection when a ping is not
+    /// acknowledged within `timeout`.
+    pub fn keep_alive_ping(mut self, interval: Duration, timeout: Duration) -> Self {
+        self.keep_alive_interval = Some(interval);
+        self.keep_alive_timeout = Some(timeout);
+        self
+    }
+
+    /// Limits the number of connections served at once. When the limit is reached, no new
+    /// connections are accepted until one closes.
+    ///
+    /// Each time the limit is hit a warning is logged, carrying a
+    /// `monotonic_counter.connection_limit_reached` field for metrics layers.
+    pub fn max_connections(mut self, connections: usize) -> Self {
+        self.max_connections = Some(connections);
+        self
+    }
+
+    pub(crate) fn connection_limit(&self) -> Option<Arc<Semaphore>> {
+        self.max_connections
+            .map(|connections| Arc::new(Semaphore::new(connections)))
+    }
+
+    pub(crate) fn supports_upgrades(&self) -> bool {
+        self.protocol == Protocol::Auto
+    }
+
+    pub(crate) fn builder(&self) -> Builder<TokioExecutor> {
+        let mut builder = Builder::new(TokioExecutor::new());
+
+        let mut http1 = builder.http1();
+        http1.timer(TokioTimer::new());
+
+        if let Some(timeout) = self.header_read_timeout {
+            http1.header_read_timeout(timeout);
+        }
+
+        if let Some(enabled) = self.keep_alive {
+            http1.keep_alive(enabled);
+        }
+
+        if let Some(bytes) = self.max_header_size {
+            http1.max_buf_size(bytes);
+        }
+
+        if let Some(count) = self.max_headers {
+            http1.max_headers(count);
+        }
+
+        let mut http2 = builder.http2();
+        http2.timer(TokioTimer::new());
+
+        if let Some(bytes) = self.max_header_size {
+            http2.max_header_list_size(u32::try_from(bytes).unwrap_or(u32::MAX));
+        }
+
+        if let Some(streams) = self.max_concurrent_streams {
+            http2.max_concurrent_streams(streams);
+        }
+
+        if let Some(bytes) = self.initial_stream_window_size {
+            http2.initial_stream_window_size(bytes);
+        }
+
+        if let Some(bytes) = self.initial_connection_window_size {
+            http2.initial_connection_window_size(bytes);
+        }
+
+        if let Some(interval) = self.keep_alive_interval {
+            http2.keep_alive_interval(interval);
+        }
+
+        if let Some(timeout) = self.keep_alive_timeout {
+            http2.keep_alive_timeout(timeout);
+        }
+
+        match self.protocol {
+            Protocol::Auto => builder,
+            Protocol::Http1Only => builder.http1_only(),
+            Protocol::Http2Only => builder.http2_only(),
+        }
+    }
+}
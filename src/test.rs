@@ -0,0 +1,254 @@
+//! Drives an [`App`] in memory, without binding a socket.
+//!
+//! ```
+//! # use std::sync::Arc;
+//! # use serde::{Deserialize, Serialize};
+//! # use taiko::prelude::*;
+//! # use taiko::test::TestClient;
+//! # #[derive(Serialize, Deserialize)]
+//! # struct NewUser { name: String }
+//! # #[derive(Deserialize)]
+//! # struct User { name: String }
+//! # async fn create(Json(user): Json<NewUser>, _state: Arc<()>) -> (StatusCode, Json<NewUser>) {
+//! #     (StatusCode::CREATED, Json(user))
+//! # }
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! # let (state, router) = ((), Router::new().post("/users", create));
+//! let client = TestClient::new(App::new(state).handler(router));
+//!
+//! let response = client
+//!     .post("/users")
+//!     .header("authorization", "Bearer token")
+//!     .json(&NewUser { name: "Ada".into() })
+//!     .await;
+//!
+//! assert_eq!(response.status(), StatusCode::CREATED);
+//! let user = response.json::<User>();
+//! # assert_eq!(user.name, "Ada");
+//! # }
+//! ```
+
+use crate::app::AppService;
+use crate::{App, Handler, Request, Response, Router};
+use http::header::{CONTENT_TYPE, HOST};
+use http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use http_body_util::{BodyExt, Full};
+use hyper::body::Bytes;
+use hyper::client::conn::http1 as client;
+use hyper::server::conn::http1 as server;
+use hyper_util::rt::TokioIo;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::future::IntoFuture;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::io::{duplex, DuplexStream};
+
+/// The size of the in-memory pipe between the client and the app.
+const PIPE_CAPACITY: usize = 64 * 1024;
+
+type Serve = dyn Fn(DuplexStream) -> Pin<Box<dyn Future<Output=()> + Send>> + Send + Sync;
+
+/// Sends requests to an [`App`] over an in-memory connection.
+///
+/// Requests go through hyper just like they would on a socket, so they reach the handlers as
+/// regular [`Request`]s. Each handler sees `127.0.0.1:0` as the client address.
+#[derive(Clone)]
+pub struct TestClient {
+    serve: Arc<Serve>,
+}
+
+impl TestClient {
+    pub fn new<S, H>(app: App<S, H>) -> Self
+    where
+        S: Send + Sync + 'static,
+        H: Handler<Request, Arc<S>, Output=Response> + Send + Sync + 'static,
+        H::Future: Send + 'static,
+    {
+        let app = app.boxed();
+        let client = SocketAddr::from((Ipv4Addr::LOCALHOST, 0));
+
+        Self {
+            serve: Arc::new(move |stream| {
+                let service = AppService(app.clone(), client);
+
+                Box::pin(async move {
+                    let _ = server::Builder::new()
+                        .serve_connection(TokioIo::new(stream), service)
+                        .await;
+                })
+            }),
+        }
+    }
+
+    /// Serves `router` with `state`, as `App::new(state).handler(router)` would.
+    pub fn with_router<S>(router: Router<S>, state: S) -> Self
+    where
+        S: Send + Sync + 'static,
+    {
+        Self::new(App::new(state).handler(router))
+    }
+
+    pub fn get(&self, uri: &str) -> TestRequest {
+        self.request(Method::GET, uri)
+    }
+
+    pub fn post(&self, uri: &str) -> TestRequest {
+        self.request(Method::POST, uri)
+    }
+
+    pub fn put(&self, uri: &str) -> TestRequest {
+        self.request(Method::PUT, uri)
+    }
+
+    pub fn patch(&self, uri: &str) -> TestRequest {
+        self.request(Method::PATCH, uri)
+    }
+
+    pub fn delete(&self, uri: &str) -> TestRequest {
+        self.request(Method::DELETE, uri)
+    }
+
+    pub fn head(&self, uri: &str) -> TestRequest {
+        self.request(Method::HEAD, uri)
+    }
+
+    pub fn options(&self, uri: &str) -> TestRequest {
+        self.request(Method::OPTIONS, uri)
+    }
+
+    pub fn request(&self, method: Method, uri: &str) -> TestRequest {
+        let request = http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .header(HOST, "localhost")
+            .body(Full::new(Bytes::new()))
+            .expect("Test request is invalid");
+
+        TestRequest {
+            serve: self.serve.clone(),
+            request,
+        }
+    }
+}
+
+/// A request being built by a [`TestClient`]. Await it to send it.
+pub struct TestRequest {
+    serve: Arc<Serve>,
+    request: http::Request<Full<Bytes>>,
+}
+
+impl TestRequest {
+    pub fn header<K, V>(mut self, name: K, value: V) -> Self
+    where
+        K: TryInto<HeaderName>,
+        K::Error: std::fmt::Debug,
+        V: TryInto<HeaderValue>,
+        V::Error: std::fmt::Debug,
+    {
+        self.request.headers_mut().append(
+            name.try_into().expect("Test header name is invalid"),
+            value.try_into().expect("Test header value is invalid"),
+        );
+        self
+    }
+
+    /// Sets the body, without touching the `Content-Type`.
+    pub fn body(mut self, body: impl Into<Bytes>) -> Self {
+        *self.request.body_mut() = Full::new(body.into());
+        self
+    }
+
+    /// Serializes `value` as the JSON body and sets the `Content-Type` to `application/json`.
+    pub fn json<T>(mut self, value: &T) -> Self
+    where
+        T: Serialize + ?Sized,
+    {
+        let bytes = serde_json::to_vec(value).expect("Test body could not serialize");
+
+        self.request
+            .headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+
+        self.body(bytes)
+    }
+
+    pub async fn send(self) -> TestResponse {
+        let (client, server) = duplex(PIPE_CAPACITY);
+        tokio::spawn((self.serve)(server));
+
+        let (mut sender, connection) = client::handshake(TokioIo::new(client))
+            .await
+            .expect("Could not connect to the app");
+        tokio::spawn(connection);
+
+        let response = sender
+            .send_request(self.request)
+            .await
+            .expect("App did not answer the request");
+
+        let (parts, body) = response.into_parts();
+        let body = body
+            .collect()
+            .await
+            .expect("Could not read the response body")
+            .to_bytes();
+
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        }
+    }
+}
+
+impl IntoFuture for TestRequest {
+    type Output = TestResponse;
+    type IntoFuture = Pin<Box<dyn Future<Output=TestResponse> + Send>>;
+
+    fn into_future(self) -> Self::IntoFuture {
+        Box::pin(self.send())
+    }
+}
+
+/// A response received by a [`TestClient`], with its body read in full.
+#[derive(Debug)]
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// The value of the header `name`, when it is present and valid UTF-8.
+    pub fn header(&self, name: impl AsRef<str>) -> Option<&str> {
+        self.headers.get(name.as_ref())?.to_str().ok()
+    }
+
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    /// The body as text. Panics when it is not valid UTF-8.
+    pub fn text(&self) -> String {
+        String::from_utf8(self.body.to_vec()).expect("Response body is not valid UTF-8")
+    }
+
+    /// The body deserialized from JSON. Panics when it does not deserialize into `T`.
+    pub fn json<T>(&self) -> T
+    where
+        T: DeserializeOwned,
+    {
+        serde_json::from_slice(&self.body).expect("Response body is not the expected JSON")
+    }
+}
@@ -0,0 +1,261 @@
+use crate::listener::Listener;
+use std::fs;
+use std::io;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::net::{TcpListener, TcpStream};
+#[cfg(unix)]
+use tokio::signal::unix::{signal, SignalKind};
+use tokio::task::{JoinHandle, JoinSet};
+use tokio::time::timeout;
+use tokio_rustls::rustls::crypto::ring::default_provider;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use tokio_rustls::rustls::server::{ClientHello, ResolvesServerCert};
+use tokio_rustls::rustls::sign::CertifiedKey;
+use tokio_rustls::rustls::ServerConfig;
+use tokio_rustls::server::TlsStream;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, error, info};
+
+/// How long a client gets to complete the TLS handshake before it is dropped.
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How many handshakes a [`TlsListener`] runs at once by default.
+const DEFAULT_MAX_HANDSHAKES: usize = 256;
+
+/// The certificate and settings used to terminate TLS.
+///
+/// HTTP/2 and HTTP/1.1 are both offered through ALPN.
+#[derive(Clone)]
+pub struct TlsConfig {
+    config: Arc<ServerConfig>,
+}
+
+impl TlsConfig {
+    /// Loads a PEM encoded certificate chain and private key from disk.
+    pub fn from_pem_file(cert: impl AsRef<Path>, key: impl AsRef<Path>) -> io::Result<Self> {
+        let (certs, key) = load_pem_files(cert.as_ref(), key.as_ref())?;
+        Self::from_der(certs, key)
+    }
+
+    /// Serves whichever certificate `certificate` currently holds, so it can be replaced
+    /// without restarting the listener.
+    pub fn reloadable(certificate: Arc<ReloadableCertificate>) -> Self {
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_cert_resolver(certificate);
+
+        Self::from(config)
+    }
+
+    /// Parses a PEM encoded certificate chain and private key.
+    pub fn from_pem(cert: &[u8], key: &[u8]) -> io::Result<Self> {
+        let certs = CertificateDer::pem_slice_iter(cert)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(io::Error::other)?;
+
+        let key = PrivateKeyDer::from_pem_slice(key).map_err(io::Error::other)?;
+
+        Self::from_der(certs, key)
+    }
+
+    fn from_der(certs: Vec<CertificateDer<'static>>, key: PrivateKeyDer<'static>) -> io::Result<Self> {
+        let config = ServerConfig::builder()
+            .with_no_client_auth()
+            .with_single_cert(certs, key)
+            .map_err(io::Error::other)?;
+
+        Ok(Self::from(config))
+    }
+}
+
+impl From<ServerConfig> for TlsConfig {
+    fn from(mut config: ServerConfig) -> Self {
+        config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+        Self {
+            config: Arc::new(config),
+        }
+    }
+}
+
+/// A certificate and private key loaded from disk that can be swapped while the server runs.
+///
+/// New handshakes use the certificate loaded last, while established connections keep the one
+/// they were made with.
+#[derive(Debug)]
+pub struct ReloadableCertificate {
+    cert: PathBuf,
+    key: PathBuf,
+    current: RwLock<Arc<CertifiedKey>>,
+}
+
+impl ReloadableCertificate {
+    pub fn from_pem_file(cert: impl Into<PathBuf>, key: impl Into<PathBuf>) -> io::Result<Arc<Self>> {
+        let (cert, key) = (cert.into(), key.into());
+        let current = certified_key(&cert, &key)?;
+
+        Ok(Arc::new(Self {
+            cert,
+            key,
+            current: RwLock::new(current),
+        }))
+    }
+
+    /// Loads the certificate and key from disk again. When they cannot be loaded, the
+    /// current certificate is kept.
+    pub fn reload(&self) -> io::Result<()> {
+        let reloaded = certified_key(&self.cert, &self.key)?;
+        *self.current.write().unwrap_or_else(PoisonError::into_inner) = reloaded;
+        Ok(())
+    }
+
+    /// Reloads the certificate whenever the process receives `SIGHUP`, or when the
+    /// modification time of either file changes, checked every `interval`.
+    pub fn watch(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        let certificate = self.clone();
+
+        tokio::spawn(async move {
+            let mut modified = certificate.modified();
+            let mut ticker = tokio::time::interval(interval);
+            #[cfg(unix)]
+            let mut hangup = signal(SignalKind::hangup()).ok();
+
+            loop {
+                #[cfg(unix)]
+                let hangup = async {
+                    match hangup.as_mut() {
+                        Some(hangup) => hangup.recv().await,
+                        None => std::future::pending().await,
+                    }
+                };
+                #[cfg(not(unix))]
+                let hangup = std::future::pending::<Option<()>>();
+
+                tokio::select! {
+                    _ = ticker.tick() => {
+                        let current = certificate.modified();
+                        if current == modified {
+                            continue;
+                        }
+                        modified = current;
+                    }
+                    Some(_) = hangup => {}
+                }
+
+                match certificate.reload() {
+                    Ok(()) => info!("Reloaded TLS certificate from {:?}", certificate.cert),
+                    Err(err) => error!("Could not reload TLS certificate: {:?}", err),
+                }
+            }
+        })
+    }
+
+    fn modified(&self) -> (Option<SystemTime>, Option<SystemTime>) {
+        let modified = |path: &Path| fs::metadata(path).and_then(|meta| meta.modified()).ok();
+        (modified(&self.cert), modified(&self.key))
+    }
+}
+
+impl ResolvesServerCert for ReloadableCertificate {
+    fn resolve(&self, _: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.current.read().unwrap_or_else(PoisonError::into_inner).clone())
+    }
+}
+
+fn load_pem_files(cert: &Path, key: &Path) -> io::Result<(Vec<CertificateDer<'static>>, PrivateKeyDer<'static>)> {
+    let certs = CertificateDer::pem_file_iter(cert)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(io::Error::other)?;
+
+    let key = PrivateKeyDer::from_pem_file(key).map_err(io::Error::other)?;
+
+    Ok((certs, key))
+}
+
+/// Loads a certificate chain and its key, checking that the key belongs to the certificate like
+/// [`TlsConfig::from_pem_file`] does, so a half-rotated pair is never installed.
+fn certified_key(cert: &Path, key: &Path) -> io::Result<Arc<CertifiedKey>> {
+    let (certs, key) = load_pem_files(cert, key)?;
+
+    if certs.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "No certificates found"));
+    }
+
+    let key = CertifiedKey::from_der(certs, key, &default_provider()).map_err(io::Error::other)?;
+
+    Ok(Arc::new(key))
+}
+
+/// A [`Listener`] that accepts TCP connections and terminates TLS on them.
+///
+/// Handshakes run in the background, so a slow client does not hold up the others. Connections
+/// only count toward [`ServerConfig::max_connections`](crate::ServerConfig::max_connections)
+/// once their handshake completes, so the handshakes in progress are limited separately, see
+/// [`TlsListener::max_handshakes`].
+pub struct TlsListener {
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    handshakes: JoinSet<io::Result<(TlsStream<TcpStream>, SocketAddr)>>,
+    max_handshakes: usize,
+}
+
+impl TlsListener {
+    pub fn new(listener: TcpListener, config: TlsConfig) -> Self {
+        Self {
+            listener,
+            acceptor: TlsAcceptor::from(config.config),
+            handshakes: JoinSet::new(),
+            max_handshakes: DEFAULT_MAX_HANDSHAKES,
+        }
+    }
+
+    /// Limits how many handshakes run at once, 256 by default. While the limit is reached, no
+    /// new TCP connections are accepted, so a flood of slow handshakes cannot exhaust the
+    /// server. Must be at least 1.
+    pub fn max_handshakes(mut self, handshakes: usize) -> Self {
+        if handshakes == 0 {
+            panic!("TLS listener must allow at least one handshake");
+        }
+
+        self.max_handshakes = handshakes;
+        self
+    }
+}
+
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    #[allow(clippy::manual_async_fn)]
+    fn accept(&mut self) -> impl Future<Output=io::Result<(Self::Io, Self::Addr)>> + Send {
+        async move {
+            loop {
+                tokio::select! {
+                    accepted = self.listener.accept(), if self.handshakes.len() < self.max_handshakes => {
+                        let (stream, client) = accepted?;
+                        let acceptor = self.acceptor.clone();
+
+                        self.handshakes.spawn(async move {
+                            let stream = timeout(HANDSHAKE_TIMEOUT, acceptor.accept(stream))
+                                .await
+                                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "TLS handshake timed out"))??;
+
+                            Ok((stream, client))
+                        });
+                    }
+                    Some(handshake) = self.handshakes.join_next() => {
+                        match handshake {
+                            Ok(Ok(connection)) => return Ok(connection),
+                            Ok(Err(err)) => debug!("TLS handshake failed: {:?}", err),
+                            Err(err) => debug!("TLS handshake task failed: {:?}", err),
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
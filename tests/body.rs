@@ -0,0 +1,290 @@
+use std::sync::Arc;
+use futures_util::StreamExt;
+use hyper::body::Bytes;
+use serde::Deserialize;
+use serde_json::Value;
+use taiko::body::{BodyLimit, Multipart, MultipartError, DEFAULT_BODY_LIMIT};
+use taiko::prelude::*;
+use taiko::test::TestClient;
+
+async fn length(body: Bytes, _state: Arc<()>) -> String {
+    body.len().to_string()
+}
+
+const BOUNDARY: &str = "taiko-boundary";
+
+/// Encodes `(name, file name, content)` fields as a `multipart/form-data` body.
+fn multipart(fields: &[(&str, Option<&str>, &[u8])]) -> Vec<u8> {
+    let mut body = Vec::new();
+
+    for (name, file_name, content) in fields {
+        body.extend_from_slice(format!("--{BOUNDARY}\r\n").as_bytes());
+
+        match file_name {
+            Some(file_name) => body.extend_from_slice(
+                format!("content-disposition: form-data; name=\"{name}\"; filename=\"{file_name}\"\r\n\r\n").as_bytes(),
+            ),
+            None => body.extend_from_slice(
+                format!("content-disposition: form-data; name=\"{name}\"\r\n\r\n").as_bytes(),
+            ),
+        }
+
+        body.extend_from_slice(content);
+        body.extend_from_slice(b"\r\n");
+    }
+
+    body.extend_from_slice(format!("--{BOUNDARY}--\r\n").as_bytes());
+    body
+}
+
+fn multipart_type() -> String {
+    format!("multipart/form-data; boundary={BOUNDARY}")
+}
+
+/// Answers with the name and length of every field, streaming their content.
+async fn upload(mut multipart: Multipart, _state: Arc<()>) -> Result<String, MultipartError> {
+    let mut fields = Vec::new();
+
+    while let Some(mut field) = multipart.next_field().await? {
+        let name = field.name().unwrap_or_default().to_string();
+        let mut length = 0;
+
+        while let Some(chunk) = field.next().await {
+            length += chunk?.len();
+        }
+
+        fields.push(format!("{name}={length}"));
+    }
+
+    Ok(fields.join(" "))
+}
+
+async fn small_fields(multipart: Multipart, state: Arc<()>) -> Result<String, MultipartError> {
+    upload(multipart.field_limit(4), state).await
+}
+
+#[derive(Deserialize)]
+struct User {
+    name: String,
+    age: u32,
+}
+
+async fn create(Json(user): Json<User>, _state: Arc<()>) -> (StatusCode, String) {
+    (StatusCode::CREATED, format!("{} {}", user.name, user.age))
+}
+
+#[derive(Deserialize)]
+struct Team {
+    members: Vec<User>,
+}
+
+async fn team(Json(team): Json<Team>, _state: Arc<()>) -> String {
+    team.members.len().to_string()
+}
+
+#[derive(Deserialize)]
+struct Profile {
+    name: String,
+    age: u32,
+}
+
+async fn profile(multipart: Multipart, _state: Arc<()>) -> Result<String, MultipartError> {
+    let profile = multipart.collect::<Profile>().await?;
+    Ok(format!("{} {}", profile.name, profile.age))
+}
+
+#[tokio::test]
+async fn body_over_the_default_limit_is_rejected() {
+    let client = TestClient::with_router(Router::new().post("/", length), ());
+
+    let response = client.post("/").body(vec![0; DEFAULT_BODY_LIMIT]).await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), DEFAULT_BODY_LIMIT.to_string());
+
+    let response = client.post("/").body(vec![0; DEFAULT_BODY_LIMIT + 1]).await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn router_limit_applies_to_its_routes() {
+    let client = TestClient::with_router(Router::new().post("/", length).body_limit(10), ());
+
+    assert_eq!(client.post("/").body(vec![0; 10]).await.status(), StatusCode::OK);
+    assert_eq!(client.post("/").body(vec![0; 11]).await.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn route_limit_overrides_router_limit() {
+    let router = Router::new()
+        .post("/small", length)
+        .post("/large", BodyLimit(100).wrap(length))
+        .body_limit(10);
+
+    let client = TestClient::with_router(router, ());
+
+    assert_eq!(client.post("/small").body(vec![0; 10]).await.status(), StatusCode::OK);
+    assert_eq!(client.post("/small").body(vec![0; 11]).await.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(client.post("/large").body(vec![0; 100]).await.status(), StatusCode::OK);
+    assert_eq!(client.post("/large").body(vec![0; 101]).await.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn nested_router_keeps_its_limit() {
+    let uploads = Router::new().post("/", length).body_limit(100);
+    let client = TestClient::with_router(Router::new().nest("/uploads", uploads).body_limit(10), ());
+
+    assert_eq!(client.post("/uploads").body(vec![0; 100]).await.status(), StatusCode::OK);
+    assert_eq!(client.post("/uploads").body(vec![0; 101]).await.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn multipart_fields_are_streamed() {
+    let client = TestClient::with_router(Router::new().post("/", upload), ());
+    let avatar = vec![7; 64 * 1024];
+
+    let response = client
+        .post("/")
+        .header("content-type", multipart_type())
+        .body(multipart(&[("name", None, b"Ada"), ("avatar", Some("ada.png"), &avatar)]))
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "name=3 avatar=65536");
+}
+
+#[tokio::test]
+async fn multipart_field_over_the_field_limit_is_rejected() {
+    let client = TestClient::with_router(Router::new().post("/", small_fields), ());
+
+    let response = client
+        .post("/")
+        .header("content-type", multipart_type())
+        .body(multipart(&[("name", None, b"Ada")]))
+        .await;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = client
+        .post("/")
+        .header("content-type", multipart_type())
+        .body(multipart(&[("name", None, b"Ada Lovelace")]))
+        .await;
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(response.text(), "Field `name` is larger than 4 bytes");
+}
+
+#[tokio::test]
+async fn multipart_body_over_the_body_limit_is_rejected() {
+    let client = TestClient::with_router(Router::new().post("/", BodyLimit(256).wrap(upload)), ());
+
+    let response = client
+        .post("/")
+        .header("content-type", multipart_type())
+        .body(multipart(&[("avatar", Some("ada.png"), &[7; 1024])]))
+        .await;
+
+    assert_eq!(response.status(), StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn multipart_requires_a_multipart_content_type() {
+    let client = TestClient::with_router(Router::new().post("/", upload), ());
+    let body = multipart(&[("name", None, b"Ada")]);
+
+    let response = client.post("/").header("content-type", "application/json").body(body.clone()).await;
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let response = client.post("/").header("content-type", "multipart/form-data").body(body).await;
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn multipart_collect_skips_file_fields() {
+    let client = TestClient::with_router(Router::new().post("/", profile), ());
+
+    let response = client
+        .post("/")
+        .header("content-type", multipart_type())
+        .body(multipart(&[
+            ("name", None, b"Ada"),
+            ("age", Some("age.txt"), b"not a number"),
+            ("age", None, b"36"),
+        ]))
+        .await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "Ada 36");
+}
+
+#[tokio::test]
+async fn json_requires_a_json_content_type() {
+    let client = TestClient::with_router(Router::new().post("/", create), ());
+
+    let response = client.post("/").body(r#"{"name":"Ada","age":36}"#).await;
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let response = client
+        .post("/")
+        .header("content-type", "text/plain")
+        .body(r#"{"name":"Ada","age":36}"#)
+        .await;
+    assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let response = client
+        .post("/")
+        .header("content-type", "application/vnd.api+json; charset=utf-8")
+        .body(r#"{"name":"Ada","age":36}"#)
+        .await;
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.text(), "Ada 36");
+}
+
+#[tokio::test]
+async fn invalid_json_is_a_bad_request() {
+    let client = TestClient::with_router(Router::new().post("/", create), ());
+
+    let response = client
+        .post("/")
+        .header("content-type", "application/json")
+        .body(r#"{"name" "Ada"}"#)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    let error = response.json::<Value>();
+    assert_eq!(error["error"], "syntax");
+    assert_eq!(error["line"], 1);
+    assert_eq!(error["column"], 9);
+
+    let response = client
+        .post("/")
+        .header("content-type", "application/json")
+        .body(r#"{"name":"Ada""#)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(response.json::<Value>()["error"], "eof");
+}
+
+#[tokio::test]
+async fn mistyped_json_is_unprocessable() {
+    let client = TestClient::with_router(Router::new().post("/", create).post("/team", team), ());
+
+    let response = client
+        .post("/")
+        .header("content-type", "application/json")
+        .body(r#"{"name":"Ada","age":"old"}"#)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    let error = response.json::<Value>();
+    assert_eq!(error["error"], "data");
+    assert_eq!(error["path"], "age");
+
+    let response = client
+        .post("/team")
+        .header("content-type", "application/json")
+        .body(r#"{"members":[{"name":"Ada","age":36},{"name":"Grace"}]}"#)
+        .await;
+
+    assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!(response.json::<Value>()["path"], "members[1]");
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use serde::Deserialize;
+use taiko::body::Body;
+use taiko::extract::Path;
+use taiko::prelude::*;
+use taiko::test::TestClient;
+
+fn text(text: String) -> Response {
+    Response::new(Body::from(text))
+}
+
+async fn user(Path(id): Path<u32>, _body: Empty, _state: Arc<()>) -> Response {
+    text(format!("user {id}"))
+}
+
+async fn pair(Path((team, id)): Path<(String, u32)>, _body: Empty, _state: Arc<()>) -> Response {
+    text(format!("{team} {id}"))
+}
+
+#[derive(Deserialize)]
+struct MemberPath {
+    team: String,
+    id: u32,
+}
+
+async fn member(Path(path): Path<MemberPath>, _body: Empty, _state: Arc<()>) -> Response {
+    text(format!("{} {}", path.team, path.id))
+}
+
+#[tokio::test]
+async fn path_extracts_a_single_value() {
+    let client = TestClient::with_router(Router::new().get("/users/{id}", user), ());
+
+    let response = client.get("/users/7").await;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "user 7");
+
+    let response = client.get("/users/seven").await;
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn path_extracts_a_tuple_in_route_order() {
+    let client = TestClient::with_router(Router::new().get("/teams/{team}/members/{id}", pair), ());
+
+    let response = client.get("/teams/core/members/7").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "core 7");
+}
+
+#[tokio::test]
+async fn path_extracts_a_struct_by_name() {
+    let client = TestClient::with_router(Router::new().get("/teams/{team}/members/{id}", member), ());
+
+    let response = client.get("/teams/core/members/7").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "core 7");
+}
+
+#[tokio::test]
+async fn single_value_needs_a_single_parameter() {
+    let client = TestClient::with_router(Router::new().get("/teams/{team}/members/{id}", user), ());
+
+    let response = client.get("/teams/core/members/7").await;
+
+    assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(response.text(), "expected 1 path parameter, found 2");
+}
@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, HeaderValue};
+use http_body_util::BodyExt;
+use hyper::body::Bytes;
+use serde_json::Value;
+use taiko::body::{Body, JsonStream};
+use taiko::prelude::*;
+use taiko::response::IntoResponse;
+use taiko::test::TestClient;
+
+/// The data frames of the body, until the end or the first error.
+async fn frames(mut body: Body) -> (Vec<Bytes>, bool) {
+    let mut frames = Vec::new();
+
+    while let Some(frame) = body.frame().await {
+        match frame {
+            Ok(frame) => frames.extend(frame.into_data().ok()),
+            Err(_) => return (frames, false),
+        }
+    }
+
+    (frames, true)
+}
+
+async fn streamed<I>(items: I) -> (Vec<Bytes>, bool)
+where
+    JsonStream<I>: IntoResponse,
+{
+    let response = JsonStream(items).into_response();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["content-type"], "application/json");
+
+    frames(response.into_body()).await
+}
+
+fn joined(frames: &[Bytes]) -> Value {
+    serde_json::from_slice(&frames.concat()).unwrap()
+}
+
+#[tokio::test]
+async fn json_stream_of_nothing_is_an_empty_array() {
+    let (frames, complete) = streamed(Vec::<u32>::new()).await;
+
+    assert!(complete);
+    assert_eq!(frames.concat(), b"[]");
+}
+
+#[tokio::test]
+async fn json_stream_of_one_item() {
+    let (frames, complete) = streamed(vec!["Ada"]).await;
+
+    assert!(complete);
+    assert_eq!(frames.concat(), br#"["Ada"]"#);
+}
+
+#[tokio::test]
+async fn json_stream_sends_large_arrays_in_several_chunks() {
+    let (frames, complete) = streamed(0..10_000u32).await;
+
+    assert!(complete);
+    assert!(frames.len() > 1);
+    assert_eq!(joined(&frames), Value::from_iter(0..10_000u32));
+}
+
+#[tokio::test]
+async fn json_stream_is_cut_off_at_an_item_that_fails() {
+    let valid = BTreeMap::new();
+    let invalid = BTreeMap::from([((1, 2), "non-string key")]);
+
+    let (frames, complete) = streamed(vec![valid, invalid]).await;
+
+    assert!(!complete);
+    assert!(serde_json::from_slice::<Value>(&frames.concat()).is_err());
+}
+
+#[tokio::test]
+async fn json_that_fails_to_serialize_is_an_internal_error() {
+    let response = Json(BTreeMap::from([((1, 2), "non-string key")])).into_response();
+
+    assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(response.headers().get("content-type").is_none());
+}
+
+async fn text(response: Response) -> String {
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(body.to_vec()).unwrap()
+}
+
+#[tokio::test]
+async fn text_and_bytes_set_their_content_type() {
+    let cases = [
+        (String::from("hello").into_response(), "text/plain; charset=utf-8"),
+        ("hello".into_response(), "text/plain; charset=utf-8"),
+        (Bytes::from("hello").into_response(), "application/octet-stream"),
+        (b"hello".to_vec().into_response(), "application/octet-stream"),
+    ];
+
+    for (response, content_type) in cases {
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], content_type);
+        assert_eq!(text(response).await, "hello");
+    }
+}
+
+#[tokio::test]
+async fn status_and_unit_are_empty() {
+    let response = StatusCode::ACCEPTED.into_response();
+    assert_eq!(response.status(), StatusCode::ACCEPTED);
+    assert_eq!(text(response).await, "");
+
+    let response = ().into_response();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(text(response).await, "");
+}
+
+#[tokio::test]
+async fn none_is_not_found() {
+    assert_eq!(Some("hello").into_response().status(), StatusCode::OK);
+    assert_eq!(None::<&str>.into_response().status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn result_answers_with_either_side() {
+    let ok: Result<&str, StatusCode> = Ok("hello");
+    assert_eq!(text(ok.into_response()).await, "hello");
+
+    let err: Result<&str, StatusCode> = Err(StatusCode::CONFLICT);
+    assert_eq!(err.into_response().status(), StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn tuples_replace_status_and_headers() {
+    let response = (StatusCode::CREATED, String::from("created")).into_response();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.headers()["content-type"], "text/plain; charset=utf-8");
+    assert_eq!(text(response).await, "created");
+
+    let mut headers = HeaderMap::new();
+    headers.insert("x-id", HeaderValue::from_static("7"));
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("text/csv"));
+
+    let response = (StatusCode::CREATED, headers.clone(), "id\n7").into_response();
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.headers()["x-id"], "7");
+    assert_eq!(response.headers()["content-type"], "text/csv");
+    assert_eq!(response.headers().get_all(CONTENT_TYPE).iter().count(), 1);
+
+    let response = headers.into_response();
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.headers()["x-id"], "7");
+
+    let response = ([(CONTENT_TYPE, HeaderValue::from_static("text/html"))], "<p>hi</p>").into_response();
+    assert_eq!(response.headers()["content-type"], "text/html");
+    assert_eq!(text(response).await, "<p>hi</p>");
+}
+
+async fn create(_body: Empty, _state: Arc<()>) -> (StatusCode, String) {
+    (StatusCode::CREATED, String::from("created"))
+}
+
+#[tokio::test]
+async fn handlers_can_answer_with_any_response() {
+    let client = TestClient::with_router(Router::new().post("/", create), ());
+
+    let response = client.post("/").await;
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+    assert_eq!(response.text(), "created");
+}
@@ -0,0 +1,118 @@
+use std::sync::Arc;
+use http::Method;
+use serde::Deserialize;
+use taiko::body::Body;
+use taiko::extract::Path;
+use taiko::prelude::*;
+use taiko::test::TestClient;
+
+async fn hello(_request: Request, _state: Arc<()>) -> Response {
+    Response::new(Body::from("hello"))
+}
+
+async fn created(_request: Request, _state: Arc<()>) -> Response {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::CREATED;
+    response
+}
+
+#[derive(Deserialize)]
+struct MemberPath {
+    team: String,
+    id: u32,
+}
+
+async fn member(Path(path): Path<MemberPath>, _body: Empty, _state: Arc<()>) -> Response {
+    Response::new(Body::from(format!("{} {}", path.team, path.id)))
+}
+
+#[tokio::test]
+async fn nested_routes_see_prefix_parameters() {
+    let members = Router::new().get("/members/{id}", member);
+    let client = TestClient::with_router(Router::new().nest("/teams/{team}", members), ());
+
+    let response = client.get("/teams/core/members/7").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "core 7");
+}
+
+#[tokio::test]
+async fn nested_root_is_served_at_the_prefix() {
+    let client = TestClient::with_router(Router::new().nest("/api/", Router::new().get("/", hello)), ());
+
+    assert_eq!(client.get("/api").await.text(), "hello");
+    assert_eq!(client.get("/api/").await.status(), StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn merged_routes_keep_their_paths() {
+    let router = Router::new().get("/a", hello).merge(Router::new().get("/b", hello));
+    let client = TestClient::with_router(router, ());
+
+    assert_eq!(client.get("/a").await.status(), StatusCode::OK);
+    assert_eq!(client.get("/b").await.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn options_lists_the_allowed_methods() {
+    let client = TestClient::with_router(Router::new().get("/", hello).post("/", created), ());
+
+    let response = client.options("/").await;
+
+    assert_eq!(response.status(), StatusCode::NO_CONTENT);
+    assert_eq!(response.header("allow"), Some("GET, HEAD, OPTIONS, POST"));
+}
+
+#[tokio::test]
+async fn options_handler_replaces_the_automatic_answer() {
+    let client = TestClient::with_router(Router::new().get("/", hello).options("/", created), ());
+
+    let response = client.options("/").await;
+
+    assert_eq!(response.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn unknown_method_is_answered_with_allow() {
+    let client = TestClient::with_router(Router::new().post("/", created), ());
+
+    let response = client.delete("/").await;
+
+    assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    assert_eq!(response.header("allow"), Some("OPTIONS, POST"));
+}
+
+#[tokio::test]
+async fn head_is_answered_by_get_without_a_body() {
+    let client = TestClient::with_router(Router::new().get("/", hello), ());
+
+    let response = client.head("/").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.header("content-length"), Some("5"));
+    assert!(response.bytes().is_empty());
+}
+
+#[tokio::test]
+async fn head_handler_replaces_get() {
+    let client = TestClient::with_router(Router::new().get("/", hello).head("/", created), ());
+
+    assert_eq!(client.head("/").await.status(), StatusCode::CREATED);
+}
+
+#[tokio::test]
+async fn method_helpers_register_their_method() {
+    let router = Router::new()
+        .put("/", hello)
+        .patch("/", hello)
+        .trace("/", hello)
+        .any("/any", hello);
+
+    let client = TestClient::with_router(router, ());
+
+    assert_eq!(client.put("/").await.text(), "hello");
+    assert_eq!(client.patch("/").await.text(), "hello");
+    assert_eq!(client.request(Method::TRACE, "/").await.text(), "hello");
+    assert_eq!(client.delete("/any").await.text(), "hello");
+}
@@ -0,0 +1,113 @@
+use std::future::pending;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use taiko::body::Body;
+use taiko::prelude::*;
+use taiko::{ServerConfig, ShutdownSummary};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+use tokio::time::{sleep, timeout};
+
+type Started = mpsc::UnboundedSender<&'static str>;
+
+async fn slow(_request: Request, started: Arc<Started>) -> Response {
+    started.send("slow").unwrap();
+    sleep(Duration::from_millis(100)).await;
+    Response::new(Body::from("slow"))
+}
+
+async fn stuck(_request: Request, started: Arc<Started>) -> Response {
+    started.send("stuck").unwrap();
+    pending().await
+}
+
+async fn panics(_request: Request, started: Arc<Started>) -> Response {
+    started.send("panics").unwrap();
+    sleep(Duration::from_millis(100)).await;
+    panic!("handler failed while draining");
+}
+
+async fn hello(_request: Request, _state: Arc<()>) -> Response {
+    Response::new(Body::from("hello"))
+}
+
+/// Opens a connection and sends a `GET` for `path` without waiting for the answer.
+async fn send(addr: SocketAddr, path: &str) -> TcpStream {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!("GET {path} HTTP/1.1\r\nhost: localhost\r\n\r\n");
+    stream.write_all(request.as_bytes()).await.unwrap();
+    stream
+}
+
+/// Reads the start of the answer on a connection that stays open.
+async fn answer(stream: &mut TcpStream) -> String {
+    let mut buffer = [0; 1024];
+    let read = stream.read(&mut buffer).await.unwrap();
+    String::from_utf8_lossy(&buffer[..read]).into_owned()
+}
+
+#[tokio::test]
+async fn connections_over_the_limit_wait_for_one_to_close() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let app = App::new(())
+        .handler(Router::new().get("/", hello))
+        .server_config(ServerConfig::new().max_connections(1));
+
+    tokio::spawn(app.listen_with_shutdown(listener, pending()));
+
+    let mut first = send(addr, "/").await;
+    assert!(answer(&mut first).await.starts_with("HTTP/1.1 200 OK"));
+
+    let mut second = send(addr, "/").await;
+    assert!(timeout(Duration::from_millis(200), answer(&mut second)).await.is_err());
+
+    drop(first);
+
+    let second = timeout(Duration::from_secs(5), answer(&mut second)).await.unwrap();
+    assert!(second.starts_with("HTTP/1.1 200 OK"));
+}
+
+#[tokio::test]
+async fn shutdown_counts_drained_and_aborted_connections() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let (started, mut handlers) = mpsc::unbounded_channel();
+    let (stop, stopped) = oneshot::channel::<()>();
+
+    let router = Router::new()
+        .get("/slow", slow)
+        .get("/stuck", stuck)
+        .get("/panics", panics);
+
+    let app = App::new(started)
+        .handler(router)
+        .drain_timeout(Duration::from_millis(500));
+
+    let server = tokio::spawn(app.listen_with_shutdown(listener, async {
+        stopped.await.ok();
+    }));
+
+    let mut slow = send(addr, "/slow").await;
+    let _stuck = send(addr, "/stuck").await;
+    let _panics = send(addr, "/panics").await;
+
+    for _ in 0..3 {
+        handlers.recv().await.unwrap();
+    }
+
+    stop.send(()).unwrap();
+
+    // The panicking connection neither completed nor was still open at the deadline.
+    let summary = server.await.unwrap();
+    assert_eq!(summary, ShutdownSummary { drained: 1, aborted: 1 });
+
+    let mut response = String::new();
+    slow.read_to_string(&mut response).await.unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with("slow"));
+}
@@ -0,0 +1,88 @@
+use std::net::SocketAddr;
+use std::sync::Arc;
+use http::HeaderValue;
+use http_body_util::BodyExt;
+use serde::{Deserialize, Serialize};
+use taiko::body::Body;
+use taiko::prelude::*;
+use taiko::test::TestClient;
+
+/// Answers with the method, path, `x-name` header and body of the request, along with the
+/// request's content type and client address as headers.
+async fn echo(request: Request, _state: Arc<()>) -> Response {
+    let content_type = request.headers().get("content-type").cloned();
+    let client = request.extensions().get::<SocketAddr>().map(ToString::to_string);
+    let name = request.headers().get("x-name").cloned();
+    let line = format!("{} {}", request.method(), request.uri().path());
+
+    let body = request.into_body().collect().await.unwrap().to_bytes();
+    let name = name.as_ref().and_then(|name| name.to_str().ok()).unwrap_or_default();
+
+    let mut response = Response::new(Body::from(format!("{line} {name} {}", String::from_utf8_lossy(&body))));
+
+    if let Some(content_type) = content_type {
+        response.headers_mut().insert("x-content-type", content_type);
+    }
+
+    if let Some(client) = client {
+        response.headers_mut().insert("x-client", HeaderValue::from_str(&client).unwrap());
+    }
+
+    response
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Greeting {
+    name: String,
+}
+
+async fn greet(Json(greeting): Json<Greeting>, _state: Arc<()>) -> Json<Greeting> {
+    Json(Greeting { name: format!("Hello {}", greeting.name) })
+}
+
+async fn teapot(_request: Request, _state: Arc<()>) -> Response {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::IM_A_TEAPOT;
+    response
+}
+
+#[tokio::test]
+async fn sends_method_path_headers_and_body() {
+    let client = TestClient::with_router(Router::new().put("/echo", echo), ());
+
+    let response = client.put("/echo").header("x-name", "Ada").body("hi").await;
+
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text(), "PUT /echo Ada hi");
+}
+
+#[tokio::test]
+async fn handlers_see_a_local_client_address() {
+    let client = TestClient::with_router(Router::new().get("/echo", echo), ());
+
+    let response = client.get("/echo").await;
+
+    assert_eq!(response.header("x-client"), Some("127.0.0.1:0"));
+}
+
+#[tokio::test]
+async fn json_bodies_round_trip() {
+    let router = Router::new().post("/echo", echo).post("/greet", greet);
+    let client = TestClient::with_router(router, ());
+
+    let response = client.post("/echo").json(&Greeting { name: String::from("Ada") }).await;
+    assert_eq!(response.header("x-content-type"), Some("application/json"));
+    assert_eq!(response.text(), r#"POST /echo  {"name":"Ada"}"#);
+
+    let response = client.post("/greet").json(&Greeting { name: String::from("Ada") }).await;
+    assert_eq!(response.header("content-type"), Some("application/json"));
+    assert_eq!(response.json::<Greeting>(), Greeting { name: String::from("Hello Ada") });
+}
+
+#[tokio::test]
+async fn reports_the_response_status() {
+    let client = TestClient::new(App::new(()).handler(Router::new().get("/teapot", teapot)));
+
+    assert_eq!(client.get("/teapot").await.status(), StatusCode::IM_A_TEAPOT);
+    assert_eq!(client.get("/missing").await.status(), StatusCode::NOT_FOUND);
+}
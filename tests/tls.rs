@@ -0,0 +1,130 @@
+use std::fs;
+use std::net::SocketAddr;
+use std::process;
+use std::sync::Arc;
+use http_body_util::{BodyExt, Empty};
+use hyper::body::Bytes;
+use hyper::client::conn::http1;
+use hyper_util::rt::TokioIo;
+use taiko::body::Body;
+use taiko::prelude::*;
+use taiko::tls::{ReloadableCertificate, TlsConfig};
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::client::TlsStream;
+use tokio_rustls::rustls::pki_types::pem::PemObject;
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName};
+use tokio_rustls::rustls::{ClientConfig, RootCertStore};
+use tokio_rustls::TlsConnector;
+
+const CA: &[u8] = include_bytes!("fixtures/ca.pem");
+const CERT: &[u8] = include_bytes!("fixtures/cert.pem");
+const KEY: &[u8] = include_bytes!("fixtures/key.pem");
+const CERT2: &[u8] = include_bytes!("fixtures/cert2.pem");
+const KEY2: &[u8] = include_bytes!("fixtures/key2.pem");
+
+async fn hello(_request: Request, _state: Arc<()>) -> Response {
+    Response::new(Body::from("hello over tls"))
+}
+
+async fn serve(config: TlsConfig) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let app = App::new(()).handler(Router::new().get("/", hello));
+    tokio::spawn(app.listen_tls(listener, config));
+
+    addr
+}
+
+fn connector(alpn: &[&[u8]]) -> TlsConnector {
+    let mut roots = RootCertStore::empty();
+    roots.add(CertificateDer::from_pem_slice(CA).unwrap()).unwrap();
+
+    let mut config = ClientConfig::builder()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    config.alpn_protocols = alpn.iter().map(|protocol| protocol.to_vec()).collect();
+
+    TlsConnector::from(Arc::new(config))
+}
+
+async fn connect(addr: SocketAddr, alpn: &[&[u8]]) -> TlsStream<TcpStream> {
+    let stream = TcpStream::connect(addr).await.unwrap();
+
+    connector(alpn)
+        .connect(ServerName::try_from("localhost").unwrap(), stream)
+        .await
+        .unwrap()
+}
+
+async fn served_certificate(addr: SocketAddr) -> CertificateDer<'static> {
+    let stream = connect(addr, &[]).await;
+
+    stream.get_ref().1.peer_certificates().unwrap()[0].clone().into_owned()
+}
+
+#[tokio::test]
+async fn serves_http1_over_tls() {
+    let addr = serve(TlsConfig::from_pem(CERT, KEY).unwrap()).await;
+
+    let stream = connect(addr, &[b"http/1.1"]).await;
+
+    let (mut sender, connection) = http1::handshake(TokioIo::new(stream)).await.unwrap();
+    tokio::spawn(connection);
+
+    let request = http::Request::get("/")
+        .header("host", "localhost")
+        .body(Empty::<Bytes>::new())
+        .unwrap();
+
+    let response = sender.send_request(request).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let body = response.into_body().collect().await.unwrap().to_bytes();
+    assert_eq!(body, "hello over tls");
+}
+
+#[tokio::test]
+async fn negotiates_http2_through_alpn() {
+    let addr = serve(TlsConfig::from_pem(CERT, KEY).unwrap()).await;
+
+    let stream = connect(addr, &[b"h2", b"http/1.1"]).await;
+
+    assert_eq!(stream.get_ref().1.alpn_protocol(), Some(&b"h2"[..]));
+}
+
+#[tokio::test]
+async fn rejects_a_key_that_does_not_match_the_certificate() {
+    assert!(TlsConfig::from_pem(CERT, KEY2).is_err());
+}
+
+#[tokio::test]
+async fn reload_swaps_the_served_certificate() {
+    let dir = std::env::temp_dir().join(format!("taiko-tls-reload-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+
+    let cert = dir.join("cert.pem");
+    let key = dir.join("key.pem");
+    fs::write(&cert, CERT).unwrap();
+    fs::write(&key, KEY).unwrap();
+
+    let certificate = ReloadableCertificate::from_pem_file(&cert, &key).unwrap();
+    let addr = serve(TlsConfig::reloadable(certificate.clone())).await;
+
+    let first = CertificateDer::from_pem_slice(CERT).unwrap();
+    let second = CertificateDer::from_pem_slice(CERT2).unwrap();
+    assert_eq!(served_certificate(addr).await, first);
+
+    fs::write(&cert, CERT2).unwrap();
+    fs::write(&key, KEY2).unwrap();
+    certificate.reload().unwrap();
+    assert_eq!(served_certificate(addr).await, second);
+
+    // A pair that does not match is refused and the last good certificate stays in place.
+    fs::write(&key, KEY).unwrap();
+    assert!(certificate.reload().is_err());
+    assert_eq!(served_certificate(addr).await, second);
+
+    fs::remove_dir_all(&dir).unwrap();
+}